@@ -0,0 +1,34 @@
+// returns the value that follows `flag` on the command line, e.g. `--seed 42`
+pub fn arg_value(flag: &str) -> Option<String> {
+    let args: Vec<String> = std::env::args().collect();
+    args.iter()
+        .position(|arg| arg == flag)
+        .and_then(|idx| args.get(idx + 1))
+        .cloned()
+}
+
+// true when a bare switch such as `--diagonal` was passed
+pub fn has_flag(flag: &str) -> bool {
+    std::env::args().any(|arg| arg == flag)
+}
+
+pub const USAGE: &str = "usage: dungeoncrawl [--seed <number>] [--diagonal] [--replay <file>] [--check-replay <file>] [--headless <script> [--turns <number>]]";
+
+// reports a bad command line and quits instead of starting a game
+pub fn usage_error(message: &str) -> ! {
+    eprintln!("{}", message);
+    eprintln!("{}", USAGE);
+    std::process::exit(2);
+}
+
+// the number that follows `flag`, such as `--turns 500`
+pub fn number_arg<T: std::str::FromStr>(flag: &str) -> Option<T> {
+    arg_value(flag).map(|value| {
+        value.parse().unwrap_or_else(|_| {
+            usage_error(&format!(
+                "{} must be a positive number, not \"{}\"",
+                flag, value
+            ))
+        })
+    })
+}
@@ -1,4 +1,5 @@
 pub use crate::prelude::*;
+use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -7,15 +8,15 @@ pub struct Render {
     pub glyph: FontCharType,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Player {
     pub map_level: u32,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Enemy;
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct MovingRandomly;
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -28,24 +29,176 @@ pub struct WantsToMove {
 pub struct WantsToAttack {
     pub attacker: Entity,
     pub victim: Entity,
+    // the weapon fired or item thrown, for attacks made from a distance
+    pub ranged: Option<Entity>,
 }
 
+// a shot or throw at a tile with nobody standing on it
 #[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WantsToShootTile {
+    pub attacker: Entity,
+    pub weapon: Entity,
+    pub tile: Point,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Health {
     pub current: i32,
     pub max: i32,
 }
 
-#[derive(Clone, PartialEq)]
+// xp needed to advance is this many times the current level
+pub const XP_PER_LEVEL: i32 = 10;
+
+// how many turns the level-up notice stays on the HUD
+pub const LEVEL_UP_NOTICE_TURNS: i32 = 5;
+
+// the player's character level and progress towards the next one
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Experience {
+    pub level: i32,
+    pub current: i32,
+    pub notice_turns: i32,
+}
+
+impl Default for Experience {
+    fn default() -> Self {
+        Self {
+            level: 1,
+            current: 0,
+            notice_turns: 0,
+        }
+    }
+}
+
+impl Experience {
+    pub fn next_level(&self) -> i32 {
+        self.level * XP_PER_LEVEL
+    }
+}
+
+// how much xp a monster is worth to whoever kills it
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ExperienceValue(pub i32);
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct Name(pub String);
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Description(pub String);
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct ChasingPlayer;
 
+// where a chaser last saw the player; it heads there, then looks around for `search_turns`
 #[derive(Clone, Copy, Debug, PartialEq)]
-pub struct Item;
+pub struct LastSeenPlayer {
+    pub pos: Point,
+    pub arrived: bool,
+    pub search_turns: i32,
+}
+
+// runs from the player once its health drops to this
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FleesWhenHurt {
+    pub below: i32,
+}
+
+// shoots from range and backs off if the player gets closer than `min`
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct KeepsDistance {
+    pub min: i32,
+}
 
+// walks a loop of waypoints until it spots the player
+#[derive(Clone, Debug, PartialEq)]
+pub struct Patrolling {
+    pub waypoints: Vec<Point>,
+    pub next: usize,
+}
+
+// stays by its post and only fights players who come within `radius` of it
 #[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Guarding {
+    pub post: Point,
+    pub radius: i32,
+}
+
+// does nothing until the player is seen or heard
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Sleeping;
+
+// can open closed doors by walking into them; anything else has to go around
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OpensDoors;
+
+// wades into deep water instead of going around it
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Swims;
+
+// the level's big fight; it gets a health bar on the HUD and its death goes in the run summary
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Boss;
+
+// something a monster can do instead of a plain attack once it has recharged
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum SpecialAttack {
+    // hits the player if they are within `radius`, whatever their armor
+    Slam {
+        damage: i32,
+        radius: i32,
+    },
+    // hits the player from up to `range` away if it can see them, optionally with a status
+    Bolt {
+        damage: i32,
+        range: i32,
+        status: Option<(StatusKind, i32)>,
+    },
+    // calls up to `count` of a template onto free tiles beside it
+    Summon {
+        template: String,
+        count: usize,
+    },
+}
+
+impl SpecialAttack {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SpecialAttack::Slam { .. } => "a ground-shaking slam",
+            SpecialAttack::Bolt { .. } => "a bolt of dark fire",
+            SpecialAttack::Summon { .. } => "a summoning cry",
+        }
+    }
+}
+
+// a monster's special attacks; `recharge` counts down the turns until it can use one again
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SpecialAttacks {
+    pub attacks: Vec<SpecialAttack>,
+    pub cooldown: i32,
+    pub recharge: i32,
+}
+
+// not drawn or named until the player finds it by searching or by chance
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Hidden;
+
+// springs when the player steps on it
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Trap {
+    Dart(i32),
+    Teleport,
+    Alarm,
+}
+
+// marks a wall tile that turns into a closed door once found
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SecretDoor;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Item;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct AmuletOfYala;
 
 #[derive(Clone, Debug, PartialEq)]
@@ -72,14 +225,6 @@ impl FieldOfView {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
-pub struct ProvidesHealing {
-    pub amount: i32,
-}
-
-#[derive(Clone, Copy, Debug, PartialEq)]
-pub struct ProvidesDungeonMap;
-
 #[derive(Clone, PartialEq)]
 pub struct Carried(pub Entity);
 
@@ -87,10 +232,103 @@ pub struct Carried(pub Entity);
 pub struct ActivateItem {
     pub used_by: Entity,
     pub item: Entity,
+    // where an aimed item was pointed
+    pub target: Option<Point>,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Damage(pub i32);
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Weapon;
+
+// how far a bow can shoot or an item can be thrown
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Ranged {
+    pub range: i32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Throwable;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Defense(pub i32);
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Armor;
+
+// what kind of gear an item is, as written in the templates
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum EquipKind {
+    MainHand,
+    OffHand,
+    Body,
+    Head,
+    Ring,
+}
+
+impl EquipKind {
+    pub fn slots(&self) -> &'static [EquipmentSlot] {
+        match self {
+            EquipKind::MainHand => &[EquipmentSlot::MainHand],
+            EquipKind::OffHand => &[EquipmentSlot::OffHand],
+            EquipKind::Body => &[EquipmentSlot::Body],
+            EquipKind::Head => &[EquipmentSlot::Head],
+            EquipKind::Ring => &[EquipmentSlot::LeftRing, EquipmentSlot::RightRing],
+        }
+    }
+}
+
+// where a piece of gear is worn
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum EquipmentSlot {
+    MainHand,
+    OffHand,
+    Body,
+    Head,
+    LeftRing,
+    RightRing,
+}
+
+impl EquipmentSlot {
+    pub const ALL: [EquipmentSlot; 6] = [
+        EquipmentSlot::MainHand,
+        EquipmentSlot::OffHand,
+        EquipmentSlot::Body,
+        EquipmentSlot::Head,
+        EquipmentSlot::LeftRing,
+        EquipmentSlot::RightRing,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            EquipmentSlot::MainHand => "Main hand",
+            EquipmentSlot::OffHand => "Off hand",
+            EquipmentSlot::Body => "Body",
+            EquipmentSlot::Head => "Head",
+            EquipmentSlot::LeftRing => "Left ring",
+            EquipmentSlot::RightRing => "Right ring",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Equippable(pub EquipKind);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Equipped {
+    pub owner: Entity,
+    pub slot: EquipmentSlot,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Equip {
+    pub owner: Entity,
+    pub item: Entity,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Unequip {
+    pub owner: Entity,
+    pub item: Entity,
+}
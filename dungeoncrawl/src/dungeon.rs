@@ -0,0 +1,54 @@
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+// a level the player has left, kept exactly as it was for when they come back
+#[derive(Clone, Serialize, Deserialize)]
+pub struct StoredLevel {
+    pub map: Map,
+    pub theme: ThemeKind,
+    pub entities: Vec<SavedEntity>,
+}
+
+// every level generated so far apart from the one being played, keyed by map level
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Dungeon {
+    pub levels: HashMap<u32, StoredLevel>,
+    // unique monsters that have turned up somewhere this run, so they never turn up again
+    #[serde(default)]
+    pub uniques: HashSet<String>,
+}
+
+impl Dungeon {
+    // packs up everything on the current level except the player and what they carry
+    pub fn store(&mut self, ecs: &World, resources: &Resources, map_level: u32) {
+        let player = *<Entity>::query()
+            .filter(component::<Player>())
+            .iter(ecs)
+            .next()
+            .unwrap();
+        let entities: Vec<Entity> = <(Entity, &Name, Option<&Carried>)>::query()
+            .iter(ecs)
+            .filter(|(entity, _, carried)| {
+                **entity != player && carried.map_or(true, |carried| carried.0 != player)
+            })
+            .map(|(entity, _, _)| *entity)
+            .collect();
+
+        self.levels.insert(
+            map_level,
+            StoredLevel {
+                map: resources.get::<Map>().unwrap().clone(),
+                theme: resources.get::<Box<dyn MapTheme>>().unwrap().kind(),
+                entities: capture_entities(ecs, &entities),
+            },
+        );
+    }
+
+    // brings a stored level's entities back into the world, handing back its map and theme
+    pub fn restore(&mut self, ecs: &mut World, map_level: u32) -> Option<(Map, ThemeKind)> {
+        let level = self.levels.remove(&map_level)?;
+        restore_entities(ecs, &level.entities);
+        Some((level.map, level.theme))
+    }
+}
@@ -0,0 +1,58 @@
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+
+// what an item does when used; templates list these directly, so a new effect
+// only needs a variant here and a handler in use_items
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Effect {
+    Healing(i32),
+    MagicMap,
+    Status(StatusKind, i32),
+    Teleport,
+    Fireball { damage: i32, radius: i32 },
+    Fear(i32),
+    RevealMonsters(i32),
+}
+
+impl Effect {
+    // effects aimed at a spot go through the targeting mode first
+    pub fn needs_target(&self) -> bool {
+        matches!(self, Effect::Fireball { .. })
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            Effect::Healing(amount) => format!("Restores {} hp.", amount),
+            Effect::MagicMap => "Reveals the whole level.".to_string(),
+            Effect::Status(kind, turns) => format!("{} for {} turns.", kind.label(), turns),
+            Effect::Teleport => "Carries you to a random spot on the level.".to_string(),
+            Effect::Fireball { damage, radius } => format!(
+                "Deals {} damage to everything within {} tiles of the target.",
+                damage, radius
+            ),
+            Effect::Fear(turns) => format!(
+                "Sends the monsters you can see fleeing for {} turns.",
+                turns
+            ),
+            Effect::RevealMonsters(turns) => {
+                format!("Shows every monster on the level for {} turns.", turns)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Provides(pub Vec<Effect>);
+
+// throwables and targeted scrolls need a target picked before they can be used
+pub fn needs_target(ecs: &SubWorld, item: Entity) -> bool {
+    ecs.entry_ref(item)
+        .map(|entry| {
+            entry.get_component::<Throwable>().is_ok()
+                || entry
+                    .get_component::<Provides>()
+                    .map(|provides| provides.0.iter().any(|effect| effect.needs_target()))
+                    .unwrap_or(false)
+        })
+        .unwrap_or(false)
+}
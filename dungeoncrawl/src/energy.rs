@@ -0,0 +1,38 @@
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+
+// every action costs this much energy, and normal speed earns exactly that each round
+pub const ACTION_COST: i32 = 10;
+pub const NORMAL_SPEED: i32 = 10;
+
+// energy earned each round, before haste or slow
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Speed(pub i32);
+
+// banked energy; an entity gets to act whenever it holds at least ACTION_COST
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Energy(pub i32);
+
+impl Energy {
+    pub fn ready(&self) -> bool {
+        self.0 >= ACTION_COST
+    }
+}
+
+// already used this round's action out of turn, such as on a special attack; cleared when it is paid for
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Acted;
+
+// callers need read access to Energy and Acted; entities outside the scheduler always act
+pub fn can_act(ecs: &SubWorld, entity: Entity) -> bool {
+    ecs.entry_ref(entity)
+        .ok()
+        .map(|entry| {
+            entry.get_component::<Acted>().is_err()
+                && entry
+                    .get_component::<Energy>()
+                    .map(|energy| energy.ready())
+                    .unwrap_or(true)
+        })
+        .unwrap_or(false)
+}
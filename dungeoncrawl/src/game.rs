@@ -0,0 +1,266 @@
+use crate::prelude::*;
+use std::collections::HashSet;
+
+// whether the left mouse button went down this frame
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MouseClick(pub bool);
+
+// the world, its resources and the turn schedules; shared by the window and the headless runner
+pub struct Game {
+    pub ecs: World,
+    pub resources: Resources,
+    // windowed games record their inputs so a run can be replayed later
+    pub recorder: Option<Replay>,
+    // four or eight-way movement, carried onto every map this game builds
+    pub movement: Movement,
+    input_systems: Schedule,
+    player_systems: Schedule,
+    monster_systems: Schedule,
+    log_systems: Schedule,
+    inventory_systems: Schedule,
+    targeting_systems: Schedule,
+}
+
+impl Game {
+    pub fn new(seed: Seed, movement: Movement, headless: bool) -> Self {
+        let mut game = Self {
+            ecs: World::default(),
+            resources: Resources::default(),
+            recorder: if headless {
+                None
+            } else {
+                Some(Replay::new(seed, movement))
+            },
+            movement,
+            input_systems: build_input_scheduler(headless),
+            player_systems: build_player_scheduler(headless),
+            monster_systems: build_monster_scheduler(headless),
+            log_systems: build_log_scheduler(headless),
+            inventory_systems: build_inventory_scheduler(headless),
+            targeting_systems: build_targeting_scheduler(headless),
+        };
+        game.start(seed);
+        game
+    }
+
+    pub fn start(&mut self, seed: Seed) {
+        self.ecs = World::default();
+        self.resources = Resources::default();
+        self.resources.insert(seed.rng());
+        let templates = Templates::load();
+        let vaults = Vaults::load();
+        self.resources
+            .insert(Progression::load(&templates, &vaults));
+        self.resources.insert(templates);
+        self.resources.insert(vaults);
+        let mut dungeon = Dungeon::default();
+        let map_builder = self.build_level(0, &mut dungeon);
+        spawn_player(&mut self.ecs, map_builder.player_start);
+        self.resources.insert(map_builder.map);
+        self.resources.insert(Camera::new(map_builder.player_start));
+        self.resources.insert(TurnState::AwaitingInput);
+        self.resources.insert(map_builder.theme);
+        self.resources.insert(seed);
+        let mut log = GameLog::default();
+        log.push(GameEvent::EnteredLevel { level: 1 });
+        self.resources.insert(log);
+        self.resources.insert(dungeon);
+        self.resources.insert(RunSummary::default());
+        self.resources.insert(InventoryCursor::default());
+        self.resources.insert(Targeting::default());
+        if self.recorder.is_some() {
+            self.recorder = Some(Replay::new(seed, self.movement));
+        }
+    }
+
+    pub fn load(&mut self, save: SaveGame) {
+        self.ecs = World::default();
+        self.resources = Resources::default();
+        if self.recorder.is_some() {
+            self.recorder = Some(Replay::continued(save.clone()));
+        }
+        self.movement = save.map.movement;
+        save.restore(&mut self.ecs, &mut self.resources);
+    }
+
+    pub fn reset(&mut self) {
+        // draw the next run's seed from the current one so a whole session stays reproducible
+        let seed = Seed(
+            self.resources
+                .get_mut::<RandomNumberGenerator>()
+                .unwrap()
+                .next_u64(),
+        );
+        self.start(seed);
+    }
+
+    pub fn turn_state(&self) -> TurnState {
+        *self.resources.get::<TurnState>().unwrap()
+    }
+
+    pub fn tick(&mut self, key: Option<VirtualKeyCode>, mouse_pos: Point, clicked: bool) {
+        self.resources.insert(key);
+        self.resources.insert(mouse_pos);
+        self.resources.insert(MouseClick(clicked));
+
+        if self.turn_state().accepts_input() && (key.is_some() || clicked) {
+            if let Some(recorder) = &mut self.recorder {
+                recorder.record(key, mouse_pos, clicked);
+            }
+        }
+
+        match self.turn_state() {
+            TurnState::AwaitingInput => self
+                .input_systems
+                .execute(&mut self.ecs, &mut self.resources),
+            TurnState::PlayerTurn => self
+                .player_systems
+                .execute(&mut self.ecs, &mut self.resources),
+            TurnState::MonsterTurn => self
+                .monster_systems
+                .execute(&mut self.ecs, &mut self.resources),
+            TurnState::ViewingLog => self.log_systems.execute(&mut self.ecs, &mut self.resources),
+            TurnState::Inventory => self
+                .inventory_systems
+                .execute(&mut self.ecs, &mut self.resources),
+            TurnState::Targeting => self
+                .targeting_systems
+                .execute(&mut self.ecs, &mut self.resources),
+            TurnState::NextLevel => {
+                let map_level = self.map_level();
+                // whatever sent the player down, there is no going deeper than the amulet
+                let amulet_level = self.resources.get::<Progression>().unwrap().amulet_level();
+                if map_level < amulet_level {
+                    self.change_level(map_level + 1);
+                } else {
+                    self.resources.insert(TurnState::AwaitingInput);
+                }
+            }
+            TurnState::PreviousLevel => {
+                let map_level = self.map_level();
+                self.change_level(map_level - 1);
+            }
+            TurnState::MainMenu | TurnState::GameOver | TurnState::Victory => {}
+        }
+    }
+
+    fn map_level(&self) -> u32 {
+        <&Player>::query()
+            .iter(&self.ecs)
+            .map(|player| player.map_level)
+            .next()
+            .unwrap_or(0)
+    }
+
+    // puts the current level away and moves the player onto `map_level`, building it if it's new
+    fn change_level(&mut self, map_level: u32) {
+        let current_level = self.map_level();
+        let mut dungeon = self.resources.remove::<Dungeon>().unwrap();
+        dungeon.store(&self.ecs, &self.resources, current_level);
+
+        // remove every entity that is not a player or the player's items
+        let player_entity = *<Entity>::query()
+            .filter(component::<Player>())
+            .iter(&self.ecs)
+            .next()
+            .unwrap();
+
+        let mut entities_to_keep = HashSet::new();
+        entities_to_keep.insert(player_entity);
+        <(Entity, &Carried)>::query()
+            .iter(&self.ecs)
+            .filter(|(_, carried)| carried.0 == player_entity)
+            .for_each(|(e, _)| {
+                entities_to_keep.insert(*e);
+            });
+
+        let mut cb = CommandBuffer::new(&self.ecs);
+        for e in Entity::query().iter(&self.ecs) {
+            if !entities_to_keep.contains(e) {
+                cb.remove(*e);
+            }
+        }
+        cb.flush(&mut self.ecs);
+
+        // set the player's is_dirty flag to make sure the next level renders correctly
+        <&mut FieldOfView>::query()
+            .iter_mut(&mut self.ecs)
+            .for_each(|fov| fov.is_dirty = true);
+
+        let (map, theme) = match dungeon.restore(&mut self.ecs, map_level) {
+            Some((map, theme)) => (map, theme.build()),
+            None => {
+                let map_builder = self.build_level(map_level, &mut dungeon);
+                (map_builder.map, map_builder.theme)
+            }
+        };
+        self.resources.insert(dungeon);
+
+        // arrive on the stairs that lead back the way the player came
+        let arrival_tile = if map_level > current_level {
+            TileType::UpStairs
+        } else {
+            TileType::Exit
+        };
+        let arrival = map.index_to_point2d(
+            map.tiles
+                .iter()
+                .position(|tile| *tile == arrival_tile)
+                .expect("Level has no stairs to arrive on"),
+        );
+        <(&mut Player, &mut Point)>::query()
+            .iter_mut(&mut self.ecs)
+            .for_each(|(player, pos)| {
+                player.map_level = map_level;
+                *pos = arrival;
+            });
+
+        self.resources.insert(map);
+        self.resources.insert(Camera::new(arrival));
+        self.resources.insert(TurnState::AwaitingInput);
+        self.resources.insert(theme);
+        self.resources
+            .get_mut::<GameLog>()
+            .unwrap()
+            .push(GameEvent::EnteredLevel {
+                level: map_level + 1,
+            });
+    }
+
+    // generates a level the player hasn't been to yet and spawns everything on it
+    fn build_level(&mut self, map_level: u32, dungeon: &mut Dungeon) -> MapBuilder {
+        let mut rng = self.resources.remove::<RandomNumberGenerator>().unwrap();
+        let progression = self.resources.remove::<Progression>().unwrap();
+        let mut map_builder = MapBuilder::new(
+            &mut rng,
+            &progression,
+            &self.resources.get::<Vaults>().unwrap(),
+            map_level,
+        );
+        map_builder.map.movement = self.movement;
+
+        // the last level holds the amulet instead of an exit
+        if map_level == progression.amulet_level() {
+            spawn_amulet_of_yala(&mut self.ecs, map_builder.amulet_start);
+        } else {
+            let exit_idx = map_builder.map.point2d_to_index(map_builder.amulet_start);
+            map_builder.map.tiles[exit_idx] = TileType::Exit;
+        }
+        if map_level > 0 {
+            let stairs_idx = map_builder.map.point2d_to_index(map_builder.player_start);
+            map_builder.map.tiles[stairs_idx] = TileType::UpStairs;
+        }
+
+        spawn_level(
+            &mut self.ecs,
+            &mut rng,
+            &self.resources.get::<Templates>().unwrap(),
+            progression.level(map_level),
+            &map_builder,
+            &mut dungeon.uniques,
+        );
+        self.resources.insert(rng);
+        self.resources.insert(progression);
+        map_builder
+    }
+}
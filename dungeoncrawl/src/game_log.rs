@@ -0,0 +1,208 @@
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub const HUD_LOG_LINES: usize = 5;
+pub const LOG_PAGE_LINES: usize = 90;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum GameEvent {
+    Attack {
+        attacker: String,
+        victim: String,
+        damage: i32,
+    },
+    Killed {
+        attacker: String,
+        victim: String,
+    },
+    PickedUp {
+        item: String,
+    },
+    Equipped {
+        item: String,
+        slot: EquipmentSlot,
+    },
+    Unequipped {
+        item: String,
+    },
+    Dropped {
+        item: String,
+    },
+    Threw {
+        item: String,
+    },
+    OutOfRange {
+        attacker: String,
+        victim: String,
+    },
+    NoTarget,
+    ShotMissed {
+        weapon: String,
+    },
+    NoRangedWeapon,
+    NothingToThrow,
+    OpenedDoor,
+    ClosedDoor,
+    NoDoorToClose,
+    FellIntoChasm,
+    FoundTrap {
+        trap: String,
+    },
+    FoundSecretDoor,
+    FoundNothing,
+    SprungTrap {
+        trap: String,
+    },
+    AlarmSounded,
+    UsedItem {
+        item: String,
+    },
+    Healed {
+        amount: i32,
+    },
+    RevealedMap,
+    Teleported,
+    StatusApplied {
+        target: String,
+        status: StatusKind,
+    },
+    StatusEnded {
+        target: String,
+        status: StatusKind,
+    },
+    WokeUp {
+        monster: String,
+    },
+    UsedSpecial {
+        attacker: String,
+        attack: String,
+    },
+    Summoned {
+        minion: String,
+    },
+    LevelUp {
+        level: i32,
+    },
+    EnteredLevel {
+        level: u32,
+    },
+    SaveUnreadable,
+}
+
+impl GameEvent {
+    pub fn text(&self) -> String {
+        match self {
+            GameEvent::Attack {
+                attacker,
+                victim,
+                damage,
+            } => format!("{} takes {} damage from {}.", victim, damage, attacker),
+            GameEvent::Killed { attacker, victim } => {
+                format!("{} is slain by {}.", victim, attacker)
+            }
+            GameEvent::PickedUp { item } => format!("You pick up the {}.", item),
+            GameEvent::Equipped { item, slot } => {
+                format!("You equip the {} ({}).", item, slot.label())
+            }
+            GameEvent::Unequipped { item } => format!("You put the {} in your pack.", item),
+            GameEvent::Dropped { item } => format!("You drop the {}.", item),
+            GameEvent::Threw { item } => format!("You throw the {}.", item),
+            GameEvent::OutOfRange { attacker, victim } => {
+                format!("{} can't reach {} from there.", attacker, victim)
+            }
+            GameEvent::NoTarget => "There is nothing in range to aim at.".to_string(),
+            GameEvent::ShotMissed { weapon } => {
+                format!("Your shot from the {} hits nothing.", weapon)
+            }
+            GameEvent::NoRangedWeapon => "You have no ranged weapon ready.".to_string(),
+            GameEvent::NothingToThrow => "You have nothing to throw.".to_string(),
+            GameEvent::OpenedDoor => "You open the door.".to_string(),
+            GameEvent::ClosedDoor => "You close the door.".to_string(),
+            GameEvent::NoDoorToClose => "There is no open door next to you.".to_string(),
+            GameEvent::FellIntoChasm => "You plunge into the chasm!".to_string(),
+            GameEvent::FoundTrap { trap } => format!("You spot the {}.", trap),
+            GameEvent::FoundSecretDoor => "You find a secret door!".to_string(),
+            GameEvent::FoundNothing => "You search but find nothing.".to_string(),
+            GameEvent::SprungTrap { trap } => format!("You spring the {}!", trap),
+            GameEvent::AlarmSounded => "An alarm rings out through the level!".to_string(),
+            GameEvent::UsedItem { item } => format!("You use the {}.", item),
+            GameEvent::Healed { amount } => format!("You recover {} hp.", amount),
+            GameEvent::RevealedMap => "The layout of the level is revealed.".to_string(),
+            GameEvent::Teleported => {
+                "The world lurches and you find yourself elsewhere.".to_string()
+            }
+            GameEvent::StatusApplied { target, status } => {
+                format!("{} is {}.", target, status.adjective())
+            }
+            GameEvent::StatusEnded { target, status } => {
+                format!("{} is no longer {}.", target, status.adjective())
+            }
+            GameEvent::WokeUp { monster } => format!("{} wakes up.", monster),
+            GameEvent::UsedSpecial { attacker, attack } => {
+                format!("{} unleashes {}!", attacker, attack)
+            }
+            GameEvent::Summoned { minion } => format!("A {} answers the call!", minion),
+            GameEvent::LevelUp { level } => {
+                format!("You feel stronger! You reach level {}.", level)
+            }
+            GameEvent::EnteredLevel { level } => format!("You enter dungeon level {}.", level),
+            GameEvent::SaveUnreadable => {
+                "Your saved run could not be read, so a new one begins.".to_string()
+            }
+        }
+    }
+
+    pub fn color(&self) -> ColorPair {
+        let fg = match self {
+            GameEvent::Attack { .. } => ORANGE,
+            GameEvent::Killed { .. } => RED,
+            GameEvent::PickedUp { .. }
+            | GameEvent::Equipped { .. }
+            | GameEvent::Unequipped { .. }
+            | GameEvent::Dropped { .. }
+            | GameEvent::Threw { .. } => CYAN,
+            GameEvent::OutOfRange { .. }
+            | GameEvent::NoTarget
+            | GameEvent::ShotMissed { .. }
+            | GameEvent::NoRangedWeapon
+            | GameEvent::NothingToThrow
+            | GameEvent::NoDoorToClose
+            | GameEvent::FoundNothing => GRAY,
+            GameEvent::OpenedDoor | GameEvent::ClosedDoor | GameEvent::FoundSecretDoor => BURLYWOOD,
+            GameEvent::FoundTrap { .. } => CYAN,
+            GameEvent::SprungTrap { .. } | GameEvent::AlarmSounded => RED,
+            GameEvent::UsedItem { .. } | GameEvent::Healed { .. } => GREEN,
+            GameEvent::RevealedMap | GameEvent::Teleported | GameEvent::FellIntoChasm => MAGENTA,
+            GameEvent::EnteredLevel { .. } => YELLOW,
+            GameEvent::SaveUnreadable => RED,
+            GameEvent::LevelUp { .. } => GOLD,
+            GameEvent::StatusApplied { status, .. } => status.color(),
+            GameEvent::StatusEnded { .. } => GRAY,
+            GameEvent::WokeUp { .. } => YELLOW,
+            GameEvent::UsedSpecial { .. } | GameEvent::Summoned { .. } => PURPLE,
+        };
+        ColorPair::new(fg, BLACK)
+    }
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct GameLog {
+    pub entries: Vec<GameEvent>,
+    // how many lines the log viewer is scrolled back from the newest entry
+    #[serde(skip)]
+    pub scroll: usize,
+}
+
+impl GameLog {
+    pub fn push(&mut self, event: GameEvent) {
+        self.entries.push(event);
+    }
+
+    pub fn recent(&self, count: usize) -> &[GameEvent] {
+        &self.entries[self.entries.len().saturating_sub(count)..]
+    }
+
+    pub fn max_scroll(&self) -> usize {
+        self.entries.len().saturating_sub(LOG_PAGE_LINES)
+    }
+}
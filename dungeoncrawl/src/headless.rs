@@ -0,0 +1,88 @@
+use crate::prelude::*;
+use std::fs::read_to_string;
+
+// one key name per whitespace-separated token; anything after a '#' is a comment
+pub fn load_script(path: &str) -> Result<Vec<VirtualKeyCode>, String> {
+    read_to_string(path)
+        .map_err(|err| format!("Failed opening script {}: {}", path, err))?
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or(""))
+        .flat_map(|line| line.split_whitespace())
+        .map(|name| parse_key(name).ok_or_else(|| format!("Unknown key in script: {}", name)))
+        .collect()
+}
+
+// drives a Game without a BTerm: render systems are left out of its schedules
+pub struct Headless {
+    pub game: Game,
+}
+
+impl Headless {
+    pub fn new(seed: Seed, movement: Movement) -> Self {
+        Self {
+            game: Game::new(seed, movement, true),
+        }
+    }
+
+    pub fn from_replay(replay: &Replay) -> Self {
+        let mut headless = Self::new(replay.seed, replay.movement);
+        if let Some(save) = &replay.save {
+            headless.game.load(save.clone());
+        }
+        headless
+    }
+
+    pub fn press(&mut self, key: VirtualKeyCode, mouse_pos: Point) -> TurnState {
+        self.step(Some(key), mouse_pos, false)
+    }
+
+    // feeds one input, then steps the turn machine until it needs input again
+    pub fn step(
+        &mut self,
+        key: Option<VirtualKeyCode>,
+        mouse_pos: Point,
+        clicked: bool,
+    ) -> TurnState {
+        self.game.tick(key, mouse_pos, clicked);
+        loop {
+            match self.game.turn_state() {
+                TurnState::PlayerTurn
+                | TurnState::MonsterTurn
+                | TurnState::NextLevel
+                | TurnState::PreviousLevel => self.game.tick(None, Point::zero(), false),
+                state => return state,
+            }
+        }
+    }
+
+    // plays up to `turns` key presses, cycling through the script, and stops early if the run ends
+    pub fn run(&mut self, script: &[VirtualKeyCode], turns: usize) -> usize {
+        let mut played = 0;
+        for key in script.iter().cycle().take(turns) {
+            if !self.game.turn_state().accepts_input() {
+                break;
+            }
+            self.press(*key, Point::zero());
+            played += 1;
+        }
+        played
+    }
+
+    pub fn replay(&mut self, replay: &Replay) -> TurnState {
+        for input in replay.inputs.iter() {
+            if !self.game.turn_state().accepts_input() {
+                break;
+            }
+            self.step(input.key(), input.mouse(), input.click);
+        }
+        self.game.turn_state()
+    }
+
+    pub fn map_level(&self) -> u32 {
+        <&Player>::query()
+            .iter(&self.game.ecs)
+            .next()
+            .map(|player| player.map_level)
+            .unwrap_or(0)
+    }
+}
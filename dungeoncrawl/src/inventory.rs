@@ -0,0 +1,76 @@
+use crate::prelude::*;
+
+// which line of the inventory screen is highlighted
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct InventoryCursor(pub usize);
+
+// one line of the inventory: identical consumables share a line, gear never does
+pub struct ItemStack {
+    pub name: String,
+    pub items: Vec<Entity>,
+    pub stackable: bool,
+    pub equipped: Option<EquipmentSlot>,
+}
+
+impl ItemStack {
+    pub fn label(&self) -> String {
+        let name = if self.items.len() > 1 {
+            format!("{} x{}", self.name, self.items.len())
+        } else {
+            self.name.clone()
+        };
+        match self.equipped {
+            Some(slot) => format!("{} ({})", name, slot.label()),
+            None => name,
+        }
+    }
+}
+
+pub fn carried_stacks(ecs: &SubWorld, owner: Entity) -> Vec<ItemStack> {
+    let mut stacks: Vec<ItemStack> = Vec::new();
+    <(Entity, &Item, &Name, &Carried)>::query()
+        .iter(ecs)
+        .filter(|(_, _, _, carried)| carried.0 == owner)
+        .for_each(|(entity, _, name, _)| {
+            let entry = ecs.entry_ref(*entity).unwrap();
+            let stackable = entry.get_component::<Equippable>().is_err();
+            if stackable {
+                if let Some(stack) = stacks
+                    .iter_mut()
+                    .find(|stack| stack.stackable && stack.name == name.0)
+                {
+                    stack.items.push(*entity);
+                    return;
+                }
+            }
+            stacks.push(ItemStack {
+                name: name.0.clone(),
+                items: vec![*entity],
+                stackable,
+                equipped: entry
+                    .get_component::<Equipped>()
+                    .ok()
+                    .map(|equipped| equipped.slot),
+            });
+        });
+    stacks
+}
+
+// consumables are activated, gear is put on or taken off
+pub fn use_item(owner: Entity, item: Entity, ecs: &SubWorld, commands: &mut CommandBuffer) {
+    let entry = ecs.entry_ref(item).unwrap();
+    if entry.get_component::<Equippable>().is_err() {
+        commands.push((
+            (),
+            ActivateItem {
+                used_by: owner,
+                item,
+                target: None,
+            },
+        ));
+    } else if entry.get_component::<Equipped>().is_ok() {
+        commands.push(((), Unequip { owner, item }));
+    } else {
+        commands.push(((), Equip { owner, item }));
+    }
+}
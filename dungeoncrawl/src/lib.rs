@@ -0,0 +1,58 @@
+// the game itself, so the window in main.rs, the headless runner and tests can all drive it
+mod camera;
+mod cli;
+mod components;
+mod dungeon;
+mod effects;
+mod energy;
+mod game;
+mod game_log;
+mod headless;
+mod inventory;
+mod keys;
+mod map;
+mod map_builder;
+mod progression;
+mod replay;
+mod run_summary;
+mod save;
+mod seed;
+mod spawner;
+mod status;
+mod systems;
+mod targeting;
+mod turn_state;
+
+pub mod prelude {
+    pub use bracket_lib::prelude::*;
+    pub use legion::systems::CommandBuffer;
+    pub use legion::world::SubWorld;
+    pub use legion::*;
+    pub const SCREEN_WIDTH: i32 = 80;
+    pub const SCREEN_HEIGHT: i32 = 50;
+    pub const DISPLAY_WIDTH: i32 = SCREEN_WIDTH / 2;
+    pub const DISPLAY_HEIGHT: i32 = SCREEN_HEIGHT / 2;
+    pub use crate::camera::*;
+    pub use crate::cli::*;
+    pub use crate::components::*;
+    pub use crate::dungeon::*;
+    pub use crate::effects::*;
+    pub use crate::energy::*;
+    pub use crate::game::*;
+    pub use crate::game_log::*;
+    pub use crate::headless::*;
+    pub use crate::inventory::*;
+    pub use crate::keys::*;
+    pub use crate::map::*;
+    pub use crate::map_builder::*;
+    pub use crate::progression::*;
+    pub use crate::replay::*;
+    pub use crate::run_summary::*;
+    pub use crate::save::*;
+    pub use crate::seed::*;
+    pub use crate::spawner::*;
+    pub use crate::status::*;
+    pub use crate::systems::*;
+    pub use crate::targeting::*;
+    pub use crate::turn_state::*;
+}
@@ -1,139 +1,118 @@
-use std::collections::HashSet;
-
-mod camera;
-mod components;
-mod map;
-mod map_builder;
-mod spawner;
-mod systems;
-mod turn_state;
-
-mod prelude {
-    pub use bracket_lib::prelude::*;
-    pub use legion::systems::CommandBuffer;
-    pub use legion::world::SubWorld;
-    pub use legion::*;
-    pub const SCREEN_WIDTH: i32 = 80;
-    pub const SCREEN_HEIGHT: i32 = 50;
-    pub const DISPLAY_WIDTH: i32 = SCREEN_WIDTH / 2;
-    pub const DISPLAY_HEIGHT: i32 = SCREEN_HEIGHT / 2;
-    pub use crate::camera::*;
-    pub use crate::components::*;
-    pub use crate::map::*;
-    pub use crate::map_builder::*;
-    pub use crate::spawner::*;
-    pub use crate::systems::*;
-    pub use crate::turn_state::*;
-}
-
-use prelude::*;
+use dungeoncrawl::prelude::*;
+use std::collections::VecDeque;
 
 // STRUCTURES
 struct State {
-    ecs: World,
-    resources: Resources,
-    input_systems: Schedule,
-    player_systems: Schedule,
-    monster_systems: Schedule,
+    game: Game,
+    playback: VecDeque<ReplayInput>,
 }
 
 impl State {
-    fn new() -> Self {
-        let mut ecs = World::default();
-        let mut resources = Resources::default();
-        let mut rng = RandomNumberGenerator::new();
-        let mut map_builder = MapBuilder::new(&mut rng);
-        spawn_player(&mut ecs, map_builder.player_start);
-        //spawn_amulet_of_yala(&mut ecs, map_builder.amulet_start);
-        let exit_idx = map_builder.map.point2d_to_index(map_builder.amulet_start);
-        map_builder.map.tiles[exit_idx] = TileType::Exit;
-        spawn_level(&mut ecs, &mut rng, 0, &map_builder.monster_spawns);
-
-        resources.insert(map_builder.map);
-        resources.insert(Camera::new(map_builder.player_start));
-        resources.insert(TurnState::AwaitingInput);
-        resources.insert(map_builder.theme);
+    fn new(seed: Seed, movement: Movement) -> Self {
+        let mut game = Game::new(seed, movement, false);
+        if SaveGame::exists() {
+            game.resources.insert(TurnState::MainMenu);
+        }
         Self {
-            ecs,
-            resources,
-            input_systems: build_input_scheduler(),
-            player_systems: build_player_scheduler(),
-            monster_systems: build_monster_scheduler(),
+            game,
+            playback: VecDeque::new(),
         }
     }
 
-    fn advance_level(&mut self) {
-        // remove every entity that is not a player or the player's items
-        let player_entity = *<Entity>::query()
-            .filter(component::<Player>())
-            .iter(&mut self.ecs)
-            .nth(0)
-            .unwrap();
-
-        let mut entities_to_keep = HashSet::new();
-        entities_to_keep.insert(player_entity);
-        <(Entity, &Carried)>::query()
-            .iter(&mut self.ecs)
-            .filter(|(_, carried)| carried.0 == player_entity)
-            .for_each(|(e, _)| {
-                entities_to_keep.insert(*e);
-            });
+    fn replaying(replay: Replay) -> Self {
+        let mut game = Game::new(replay.seed, replay.movement, false);
+        if let Some(save) = replay.save {
+            game.load(save);
+        }
+        Self {
+            game,
+            playback: replay.inputs.into_iter().collect(),
+        }
+    }
 
-        let mut cb = CommandBuffer::new(&mut self.ecs);
-        for e in Entity::query().iter(&self.ecs) {
-            if !entities_to_keep.contains(e) {
-                cb.remove(*e);
+    fn check_quit(&mut self, ctx: &mut BTerm) {
+        if let Some(VirtualKeyCode::Q) = ctx.key {
+            self.save_game();
+            if let Some(recorder) = &self.game.recorder {
+                recorder.write(REPLAY_FILE);
             }
+            ctx.quitting = true;
         }
-        cb.flush(&mut self.ecs);
+    }
 
-        // set the player's is_dirty flag to make sure the next level renders correctly
-        <&mut FieldOfView>::query()
-            .iter_mut(&mut self.ecs)
-            .for_each(|fov| fov.is_dirty = true);
+    fn record_outcome(&mut self) {
+        let outcome = self.game.turn_state();
+        if let Some(recorder) = &mut self.game.recorder {
+            if recorder.outcome.is_none() {
+                recorder.outcome = Some(outcome);
+                recorder.write(REPLAY_FILE);
+            }
+        }
+    }
 
-        // generate a new map for the new level
-        let mut rng = RandomNumberGenerator::new();
-        let mut map_builder = MapBuilder::new(&mut rng);
-        let mut map_level = 0;
-        <(&mut Player, &mut Point)>::query()
-            .iter_mut(&mut self.ecs)
-            .for_each(|(player, pos)| {
-                player.map_level += 1;
-                map_level = player.map_level;
-                pos.x = map_builder.player_start.x;
-                pos.y = map_builder.player_start.y;
-            });
+    fn save_game(&self) {
+        // only a run that is still in progress is worth continuing
+        let current_state = self.game.turn_state();
+        match current_state {
+            TurnState::AwaitingInput
+            | TurnState::PlayerTurn
+            | TurnState::MonsterTurn
+            | TurnState::NextLevel
+            | TurnState::PreviousLevel
+            | TurnState::ViewingLog
+            | TurnState::Inventory => {
+                SaveGame::capture(&self.game.ecs, &self.game.resources).write(SAVE_FILE)
+            }
+            _ => {}
+        }
+    }
 
-        // check level number to decide spawning exit or amulet
-        if map_level == 2 {
-            spawn_amulet_of_yala(&mut self.ecs, map_builder.amulet_start);
-        } else {
-            let exit_idx = map_builder.map.point2d_to_index(map_builder.amulet_start);
-            map_builder.map.tiles[exit_idx] = TileType::Exit;
+    fn load_game(&mut self) {
+        match SaveGame::load(SAVE_FILE) {
+            Ok(save) => self.game.load(save),
+            // carry on with the new game that was set up behind the menu
+            Err(err) => {
+                eprintln!("{}", err);
+                self.game.resources.insert(TurnState::AwaitingInput);
+                self.game
+                    .resources
+                    .get_mut::<GameLog>()
+                    .unwrap()
+                    .push(GameEvent::SaveUnreadable);
+            }
         }
+        SaveGame::delete();
+    }
 
-        // finish the rest of the resource setup
-        spawn_level(
-            &mut self.ecs,
-            &mut rng,
-            map_level as usize,
-            &map_builder.monster_spawns,
+    fn main_menu(&mut self, ctx: &mut BTerm) {
+        ctx.set_active_console(2);
+        ctx.print_color_centered(2, YELLOW, BLACK, "Dungeon Crawler");
+        ctx.print_color_centered(
+            4,
+            WHITE,
+            BLACK,
+            "A saved run was found from the last time you quit.",
         );
+        ctx.print_color_centered(7, GREEN, BLACK, "Press 1 to start a new game.");
+        ctx.print_color_centered(8, GREEN, BLACK, "Press 2 to continue.");
 
-        self.resources.insert(map_builder.map);
-        self.resources.insert(Camera::new(map_builder.player_start));
-        self.resources.insert(TurnState::AwaitingInput);
-        self.resources.insert(map_builder.theme);
+        match ctx.key {
+            Some(VirtualKeyCode::Key1) => {
+                SaveGame::delete();
+                self.game.resources.insert(TurnState::AwaitingInput);
+            }
+            Some(VirtualKeyCode::Key2) => self.load_game(),
+            _ => {}
+        }
     }
 
-    fn check_quit(&mut self, ctx: &mut BTerm) {
-        if let Some(VirtualKeyCode::Q) = ctx.key {
-            ctx.quitting = true;
-        }
+    fn print_summary(&self, ctx: &mut BTerm, y: i32) {
+        let summary = self.game.resources.get::<RunSummary>().unwrap();
+        ctx.print_color_centered(y, GOLD, BLACK, summary.describe());
     }
 
     fn game_over(&mut self, ctx: &mut BTerm) {
+        self.record_outcome();
         ctx.set_active_console(2);
         ctx.print_color_centered(2, RED, BLACK, "Your quest ends...");
         ctx.print_color_centered(
@@ -148,6 +127,7 @@ impl State {
             BLACK,
             "The Amulet of Yala remains unclaimed, and your home town is not saved.",
         );
+        self.print_summary(ctx, 6);
         ctx.print_color_centered(
             8,
             YELLOW,
@@ -157,11 +137,12 @@ impl State {
         ctx.print_color_centered(9, GREEN, BLACK, "Press 1 to play again.");
 
         if let Some(VirtualKeyCode::Key1) = ctx.key {
-            self.reset_game_state();
+            self.game.reset();
         }
     }
 
     fn victory(&mut self, ctx: &mut BTerm) {
+        self.record_outcome();
         ctx.set_active_console(2);
         ctx.print_color_centered(2, GREEN, BLACK, "YOU WON!");
         ctx.print_color_centered(
@@ -176,28 +157,13 @@ impl State {
             BLACK,
             "Your town is saved and you can return to your normal life.",
         );
+        self.print_summary(ctx, 6);
         ctx.print_color_centered(7, GREEN, BLACK, "Press 1 to play again.");
 
         if let Some(VirtualKeyCode::Key1) = ctx.key {
-            self.reset_game_state();
+            self.game.reset();
         }
     }
-
-    fn reset_game_state(&mut self) {
-        self.ecs = World::default();
-        self.resources = Resources::default();
-        let mut rng = RandomNumberGenerator::new();
-        let mut map_builder = MapBuilder::new(&mut rng);
-        spawn_player(&mut self.ecs, map_builder.player_start);
-        //spawn_amulet_of_yala(&mut self.ecs, map_builder.amulet_start);
-        let exit_idx = map_builder.map.point2d_to_index(map_builder.amulet_start);
-        map_builder.map.tiles[exit_idx] = TileType::Exit;
-        spawn_level(&mut self.ecs, &mut rng, 0, &map_builder.monster_spawns);
-        self.resources.insert(map_builder.map);
-        self.resources.insert(Camera::new(map_builder.player_start));
-        self.resources.insert(TurnState::AwaitingInput);
-        self.resources.insert(map_builder.theme);
-    }
 }
 
 impl GameState for State {
@@ -210,35 +176,62 @@ impl GameState for State {
         ctx.set_active_console(2);
         ctx.cls();
 
-        self.resources.insert(ctx.key);
-        ctx.set_active_console(0);
-        self.resources.insert(Point::from_tuple(ctx.mouse_pos()));
+        // save before the input systems consume the quit key
+        self.check_quit(ctx);
 
-        let current_state = self.resources.get::<TurnState>().unwrap().clone();
-        match current_state {
-            TurnState::AwaitingInput => self
-                .input_systems
-                .execute(&mut self.ecs, &mut self.resources),
-            TurnState::PlayerTurn => self
-                .player_systems
-                .execute(&mut self.ecs, &mut self.resources),
-            TurnState::MonsterTurn => self
-                .monster_systems
-                .execute(&mut self.ecs, &mut self.resources),
+        ctx.set_active_console(0);
+        let mouse_pos = Point::from_tuple(ctx.mouse_pos());
+        match self.game.turn_state() {
+            TurnState::MainMenu => self.main_menu(ctx),
             TurnState::GameOver => self.game_over(ctx),
             TurnState::Victory => self.victory(ctx),
-            TurnState::NextLevel => {
-                self.advance_level();
+            state if state.accepts_input() && !self.playback.is_empty() => {
+                let input = self.playback.pop_front().unwrap();
+                self.game.tick(input.key(), input.mouse(), input.click);
             }
+            _ => self.game.tick(ctx.key, mouse_pos, ctx.left_click),
         }
 
         render_draw_buffer(ctx).expect("Render error");
-
-        self.check_quit(ctx);
     }
 }
 
 fn main() -> BError {
+    let seed = Seed::from_args();
+    let movement = Movement::from_args();
+    if let Some(script) = arg_value("--headless") {
+        let keys = load_script(&script).unwrap_or_else(|err| usage_error(&err));
+        let turns = number_arg("--turns").unwrap_or(keys.len());
+        let mut headless = Headless::new(seed, movement);
+        let played = headless.run(&keys, turns);
+        println!(
+            "Seed {}: played {} turns, reached dungeon level {}, ended in {:?}. {}",
+            seed.0,
+            played,
+            headless.map_level() + 1,
+            headless.game.turn_state(),
+            headless
+                .game
+                .resources
+                .get::<RunSummary>()
+                .unwrap()
+                .describe()
+        );
+        return Ok(());
+    }
+    if let Some(path) = arg_value("--check-replay") {
+        let replay = Replay::load(&path).unwrap_or_else(|err| usage_error(&err));
+        let outcome = Headless::from_replay(&replay).replay(&replay);
+        println!(
+            "Replayed {} inputs from seed {}: ended in {:?}, recorded {:?}",
+            replay.inputs.len(),
+            replay.seed.0,
+            outcome,
+            replay.outcome
+        );
+        return Ok(());
+    }
+
     /* ORIGINAL context OBJ
     let context = BTermBuilder::simple80x50()
         .with_title("Dungeon Crawler")
@@ -257,5 +250,14 @@ fn main() -> BError {
         .with_simple_console_no_bg(DISPLAY_WIDTH, DISPLAY_HEIGHT, "dungeonfont.png")
         .with_simple_console_no_bg(SCREEN_WIDTH * 2, SCREEN_HEIGHT * 2, "terminal8x8.png")
         .build()?;
-    main_loop(context, State::new())
+    // a replay that can't be read just means playing a fresh game instead
+    let state = match arg_value("--replay").map(|path| Replay::load(&path)) {
+        Some(Ok(replay)) => State::replaying(replay),
+        Some(Err(err)) => {
+            eprintln!("{}", err);
+            State::new(seed, movement)
+        }
+        None => State::new(seed, movement),
+    };
+    main_loop(context, state)
 }
@@ -1,55 +1,265 @@
 use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
 
-const NUM_TILES: usize = (SCREEN_WIDTH * SCREEN_HEIGHT) as usize;
-
-#[derive(Copy, Clone, PartialEq)]
+#[derive(Copy, Clone, PartialEq, Serialize, Deserialize)]
 pub enum TileType {
     Wall,
     Floor,
     Exit,
+    // leads back to the level above
+    UpStairs,
+    Door { open: bool },
+    // slow to wade through, and some monsters won't
+    DeepWater,
+    // burns whatever steps in
+    Lava,
+    // drops the player to the next level
+    Chasm,
+    // easy to cross but awkward, so paths prefer to go around
+    Rubble,
+}
+
+// pathing weights for terrain that is slower to cross than bare floor
+const WATER_COST: f32 = 2.0;
+const RUBBLE_COST: f32 = 3.0;
+
+// what a walker is able, or willing, to cross when finding its way around
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Mobility {
+    pub opens_doors: bool,
+    pub swims: bool,
+    // walks into lava and chasms
+    pub hazards: bool,
+}
+
+impl Mobility {
+    // the player goes wherever they are told to
+    pub const PLAYER: Mobility = Mobility {
+        opens_doors: true,
+        swims: true,
+        hazards: true,
+    };
+    // map generation keeps the way around free of lava and chasms
+    pub const SAFE: Mobility = Mobility {
+        opens_doors: true,
+        swims: true,
+        hazards: false,
+    };
 }
 
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Map {
+    // saves from before maps had their own size were always screen-sized
+    #[serde(default = "default_width")]
+    pub width: i32,
+    #[serde(default = "default_height")]
+    pub height: i32,
     pub tiles: Vec<TileType>,
     pub revealed_tiles: Vec<bool>,
+    #[serde(default)]
+    pub movement: Movement,
 }
 
-pub fn map_idx(x: i32, y: i32) -> usize {
-    ((y * SCREEN_WIDTH) + x) as usize
+// whether entities (and their pathfinding) may step diagonally
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Movement {
+    #[default]
+    FourWay,
+    EightWay,
+}
+
+const ORTHOGONAL: [Point; 4] = [
+    Point { x: -1, y: 0 },
+    Point { x: 1, y: 0 },
+    Point { x: 0, y: -1 },
+    Point { x: 0, y: 1 },
+];
+
+const DIAGONAL: [Point; 4] = [
+    Point { x: -1, y: -1 },
+    Point { x: 1, y: -1 },
+    Point { x: -1, y: 1 },
+    Point { x: 1, y: 1 },
+];
+
+impl Movement {
+    // `--diagonal` on the command line switches to eight-way movement
+    pub fn from_args() -> Self {
+        if has_flag("--diagonal") {
+            Movement::EightWay
+        } else {
+            Movement::FourWay
+        }
+    }
+
+    pub fn directions(&self) -> Vec<Point> {
+        match self {
+            Movement::FourWay => ORTHOGONAL.to_vec(),
+            Movement::EightWay => ORTHOGONAL.iter().chain(DIAGONAL.iter()).cloned().collect(),
+        }
+    }
+}
+
+fn default_width() -> i32 {
+    SCREEN_WIDTH
+}
+
+fn default_height() -> i32 {
+    SCREEN_HEIGHT
 }
 
 impl Map {
-    pub fn new() -> Self {
+    pub fn new(width: i32, height: i32) -> Self {
+        let num_tiles = (width * height) as usize;
         Map {
-            tiles: vec![TileType::Floor; NUM_TILES],
-            revealed_tiles: vec![false; NUM_TILES],
+            width,
+            height,
+            tiles: vec![TileType::Floor; num_tiles],
+            revealed_tiles: vec![false; num_tiles],
+            movement: Movement::default(),
         }
     }
 
     pub fn in_bounds(&self, point: Point) -> bool {
-        point.x >= 0 && point.x < SCREEN_WIDTH && point.y >= 0 && point.y < SCREEN_HEIGHT
+        point.x >= 0 && point.x < self.width && point.y >= 0 && point.y < self.height
     }
 
     pub fn can_enter_tile(&self, point: Point) -> bool {
         self.in_bounds(point)
-            && (self.tiles[map_idx(point.x, point.y)] == TileType::Floor
-                || self.tiles[map_idx(point.x, point.y)] == TileType::Exit)
+            && matches!(
+                self.tiles[self.point2d_to_index(point)],
+                TileType::Floor
+                    | TileType::Exit
+                    | TileType::UpStairs
+                    | TileType::Door { open: true }
+                    | TileType::DeepWater
+                    | TileType::Lava
+                    | TileType::Chasm
+                    | TileType::Rubble
+            )
+    }
+
+    pub fn is_closed_door(&self, point: Point) -> bool {
+        self.in_bounds(point)
+            && self.tiles[self.point2d_to_index(point)] == TileType::Door { open: false }
+    }
+
+    // the map as seen by something finding its way around it
+    pub fn pathing(&self, mobility: Mobility) -> Pathing<'_> {
+        Pathing {
+            map: self,
+            mobility,
+        }
     }
 
     pub fn try_idx(&self, point: Point) -> Option<usize> {
         if !self.in_bounds(point) {
             None
         } else {
-            Some(map_idx(point.x, point.y))
+            Some(self.point2d_to_index(point))
+        }
+    }
+
+    // a dijkstra map of the whole level around `starts`, going through doors but not hazards
+    pub fn distances(&self, starts: &[usize]) -> DijkstraMap {
+        self.pathing(Mobility::SAFE).distances(starts)
+    }
+
+    // whether the player may step there; stepping into a closed door opens it
+    pub fn can_step(&self, from: Point, to: Point) -> bool {
+        self.pathing(Mobility::PLAYER).can_step(from, to)
+    }
+}
+
+pub struct Pathing<'a> {
+    map: &'a Map,
+    mobility: Mobility,
+}
+
+impl Pathing<'_> {
+    pub fn distances(&self, starts: &[usize]) -> DijkstraMap {
+        // deep enough for the longest weighted path across the biggest level
+        DijkstraMap::new(self.map.width, self.map.height, starts, self, 4096.0)
+    }
+
+    // the first step along the cheapest path to a single spot, if there is one; goals shared by
+    // many monsters, like the player, are cheaper to reach downhill on one dijkstra map.
+    // bracket-lib's a_star_search takes 65536 steps to give up on a spot it can't reach,
+    // so this stops as soon as everything reachable has been tried
+    pub fn next_step(&self, from: Point, to: Point) -> Option<Point> {
+        if !self.map.in_bounds(from) || !self.map.in_bounds(to) || from == to {
+            return None;
+        }
+        let start = self.map.point2d_to_index(from);
+        let goal = self.map.point2d_to_index(to);
+        let mut cost = vec![f32::MAX; self.map.tiles.len()];
+        let mut came_from = vec![start; self.map.tiles.len()];
+        let mut open = BinaryHeap::new();
+        cost[start] = 0.0;
+        open.push(Frontier {
+            estimate: self.get_pathing_distance(start, goal),
+            idx: start,
+        });
+        while let Some(Frontier { idx, .. }) = open.pop() {
+            if idx == goal {
+                // walk the path back to the tile next to the start
+                let mut step = goal;
+                while came_from[step] != start {
+                    step = came_from[step];
+                }
+                return Some(self.map.index_to_point2d(step));
+            }
+            for (next, step_cost) in self.get_available_exits(idx) {
+                if cost[idx] + step_cost < cost[next] {
+                    cost[next] = cost[idx] + step_cost;
+                    came_from[next] = idx;
+                    open.push(Frontier {
+                        estimate: cost[next] + self.get_pathing_distance(next, goal),
+                        idx: next,
+                    });
+                }
+            }
+        }
+        None
+    }
+
+    // a diagonal step can't squeeze between two walls or cut a wall's corner
+    pub fn can_step(&self, from: Point, to: Point) -> bool {
+        let delta = to - from;
+        if delta.x != 0 && delta.y != 0 {
+            self.map.movement == Movement::EightWay
+                && self.map.can_enter_tile(Point::new(to.x, from.y))
+                && self.map.can_enter_tile(Point::new(from.x, to.y))
+                && self.map.can_enter_tile(to)
+                && self.cost(to).is_some()
+        } else {
+            self.cost(to).is_some()
+        }
+    }
+
+    // how expensive the tile is to walk onto, if it can be walked onto at all
+    fn cost(&self, point: Point) -> Option<f32> {
+        match self.map.tiles[self.map.try_idx(point)?] {
+            TileType::Floor
+            | TileType::Exit
+            | TileType::UpStairs
+            | TileType::Door { open: true } => Some(1.0),
+            TileType::Door { open: false } if self.mobility.opens_doors => Some(1.0),
+            TileType::DeepWater if self.mobility.swims => Some(WATER_COST),
+            TileType::Lava | TileType::Chasm if self.mobility.hazards => Some(1.0),
+            TileType::Rubble => Some(RUBBLE_COST),
+            _ => None,
         }
     }
 
-    fn valid_exit(&self, loc: Point, delta: Point) -> Option<usize> {
+    fn valid_exit(&self, loc: Point, delta: Point) -> Option<(usize, f32)> {
         let destination = loc + delta;
-        if self.in_bounds(destination) {
-            if self.can_enter_tile(destination) {
-                let idx = self.point2d_to_index(destination);
-                Some(idx)
+        if self.map.in_bounds(destination) {
+            if self.can_step(loc, destination) {
+                let idx = self.map.point2d_to_index(destination);
+                self.cost(destination).map(|cost| (idx, cost))
             } else {
                 None
             }
@@ -60,36 +270,92 @@ impl Map {
 }
 
 impl BaseMap for Map {
+    fn get_available_exits(&self, idx: usize) -> SmallVec<[(usize, f32); 10]> {
+        self.pathing(Mobility::SAFE).get_available_exits(idx)
+    }
+
+    fn get_pathing_distance(&self, idx1: usize, idx2: usize) -> f32 {
+        DistanceAlg::Pythagoras.distance2d(self.index_to_point2d(idx1), self.index_to_point2d(idx2))
+    }
+
+    fn is_opaque(&self, idx: usize) -> bool {
+        matches!(
+            self.tiles[idx],
+            TileType::Wall | TileType::Door { open: false }
+        )
+    }
+}
+
+// a tile waiting to be searched by next_step; the heap hands out the cheapest estimate first
+struct Frontier {
+    estimate: f32,
+    idx: usize,
+}
+
+impl PartialEq for Frontier {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Frontier {}
+
+impl Ord for Frontier {
+    // ties go to the lower index so the same map always yields the same path
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .estimate
+            .total_cmp(&self.estimate)
+            .then_with(|| other.idx.cmp(&self.idx))
+    }
+}
+
+impl PartialOrd for Frontier {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl BaseMap for Pathing<'_> {
     fn get_available_exits(&self, idx: usize) -> SmallVec<[(usize, f32); 10]> {
         let mut exits = SmallVec::new();
-        let location = self.index_to_point2d(idx);
+        let location = self.map.index_to_point2d(idx);
 
-        if let Some(idx) = self.valid_exit(location, Point::new(-1, 0)) {
-            exits.push((idx, 1.0))
-        }
-        if let Some(idx) = self.valid_exit(location, Point::new(1, 0)) {
-            exits.push((idx, 1.0))
-        }
-        if let Some(idx) = self.valid_exit(location, Point::new(0, -1)) {
-            exits.push((idx, 1.0))
-        }
-        if let Some(idx) = self.valid_exit(location, Point::new(0, 1)) {
-            exits.push((idx, 1.0))
-        }
+        // diagonal steps cost their true length so paths don't zigzag
+        self.map.movement.directions().iter().for_each(|delta| {
+            if let Some((idx, cost)) = self.valid_exit(location, *delta) {
+                let length = if delta.x != 0 && delta.y != 0 {
+                    std::f32::consts::SQRT_2
+                } else {
+                    1.0
+                };
+                exits.push((idx, length * cost))
+            }
+        });
         exits
     }
 
     fn get_pathing_distance(&self, idx1: usize, idx2: usize) -> f32 {
-        DistanceAlg::Pythagoras.distance2d(self.index_to_point2d(idx1), self.index_to_point2d(idx2))
+        self.map.get_pathing_distance(idx1, idx2)
     }
 
     fn is_opaque(&self, idx: usize) -> bool {
-        self.tiles[idx as usize] != TileType::Floor
+        self.map.is_opaque(idx)
+    }
+}
+
+impl Algorithm2D for Pathing<'_> {
+    fn dimensions(&self) -> Point {
+        self.map.dimensions()
+    }
+
+    fn in_bounds(&self, point: Point) -> bool {
+        self.map.in_bounds(point)
     }
 }
 impl Algorithm2D for Map {
     fn dimensions(&self) -> Point {
-        Point::new(SCREEN_WIDTH, SCREEN_HEIGHT)
+        Point::new(self.width, self.height)
     }
 
     fn in_bounds(&self, point: Point) -> bool {
@@ -1,14 +1,19 @@
-use super::MapArchitect;
+use super::{MapArchitect, NUM_MONSTERS};
 use crate::prelude::*;
 
 pub struct CellularAutomataArchitect {}
 
 impl MapArchitect for CellularAutomataArchitect {
-    fn new(&mut self, rng: &mut RandomNumberGenerator) -> MapBuilder {
+    fn new(&mut self, rng: &mut RandomNumberGenerator, width: i32, height: i32) -> MapBuilder {
         let mut mb = MapBuilder {
-            map: Map::new(),
+            map: Map::new(width, height),
             rooms: Vec::new(),
             monster_spawns: Vec::new(),
+            guard_posts: Vec::new(),
+            item_spawns: Vec::new(),
+            trap_spawns: Vec::new(),
+            secret_doors: Vec::new(),
+            boss_spawns: Vec::new(),
             player_start: Point::zero(),
             amulet_start: Point::zero(),
             theme: super::themes::DungeonTheme::new(),
@@ -18,10 +23,12 @@ impl MapArchitect for CellularAutomataArchitect {
         for _ in 0..10 {
             self.iteration(&mut mb.map);
         }
+        self.keep_largest_cave(&mut mb.map);
         let start = self.find_start(&mb.map);
-        mb.monster_spawns = mb.spawn_monsters(&start, rng);
         mb.player_start = start;
         mb.amulet_start = mb.find_most_distant();
+        mb.monster_spawns = mb.spawn_monsters(&start, rng, mb.scaled(NUM_MONSTERS));
+        mb.add_traps(rng);
 
         mb
     }
@@ -46,7 +53,7 @@ impl CellularAutomataArchitect {
         for iy in -1..=1 {
             for ix in -1..=1 {
                 if !(ix==0 && iy == 0) &&// (5)
-                    map.tiles[map_idx(x+ix, y+iy)] == TileType::Wall
+                    map.tiles[map.point2d_to_index(Point::new(x+ix, y+iy))] == TileType::Wall
                 {
                     neighbors += 1;
                 }
@@ -58,11 +65,11 @@ impl CellularAutomataArchitect {
 
     fn iteration(&mut self, map: &mut Map) {
         let mut new_tiles = map.tiles.clone(); // (6)
-        for y in 1..SCREEN_HEIGHT - 1 {
+        for y in 1..map.height - 1 {
             // (7)
-            for x in 1..SCREEN_WIDTH - 1 {
+            for x in 1..map.width - 1 {
                 let neighbors = self.count_neighbors(x, y, map); // (8)
-                let idx = map_idx(x, y);
+                let idx = map.point2d_to_index(Point::new(x, y));
                 if neighbors > 4 || neighbors == 0 {
                     // (9)
                     new_tiles[idx] = TileType::Wall;
@@ -74,8 +81,38 @@ impl CellularAutomataArchitect {
         map.tiles = new_tiles;
     }
 
+    // fills in every cave but the biggest, so the player can't start walled into a pocket
+    fn keep_largest_cave(&self, map: &mut Map) {
+        let mut seen = vec![false; map.tiles.len()];
+        let mut largest = Vec::new();
+        for idx in 0..map.tiles.len() {
+            if seen[idx] || map.tiles[idx] != TileType::Floor {
+                continue;
+            }
+            let distances = map.distances(&[idx]);
+            let cave: Vec<usize> = distances
+                .map
+                .iter()
+                .enumerate()
+                .filter(|(i, distance)| *i == idx || **distance < f32::MAX)
+                .map(|(i, _)| i)
+                .collect();
+            cave.iter().for_each(|i| seen[*i] = true);
+            if cave.len() > largest.len() {
+                largest = cave;
+            }
+        }
+        let mut keep = vec![false; map.tiles.len()];
+        largest.iter().for_each(|idx| keep[*idx] = true);
+        map.tiles
+            .iter_mut()
+            .zip(keep)
+            .filter(|(tile, keep)| **tile == TileType::Floor && !keep)
+            .for_each(|(tile, _)| *tile = TileType::Wall);
+    }
+
     fn find_start(&self, map: &Map) -> Point {
-        let center = Point::new(SCREEN_WIDTH / 2, SCREEN_HEIGHT / 2); // (10)
+        let center = Point::new(map.width / 2, map.height / 2); // (10)
         let closest_point = map
             .tiles
             .iter() // (11)
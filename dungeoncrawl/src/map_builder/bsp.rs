@@ -0,0 +1,110 @@
+use super::{MapArchitect, MAX_ROOM_HEIGHT, MAX_ROOM_WIDTH, MIN_ROOM_HEIGHT, MIN_ROOM_WIDTH};
+use crate::prelude::*;
+
+// leaves stop splitting once their longer side is no more than this
+const MAX_LEAF_SIZE: i32 = 20;
+// and are never cut shorter than this, so every leaf has space for a room and its walls
+const MIN_LEAF_SIZE: i32 = 8;
+
+pub struct BspArchitect {}
+
+impl MapArchitect for BspArchitect {
+    fn new(&mut self, rng: &mut RandomNumberGenerator, width: i32, height: i32) -> MapBuilder {
+        let mut mb = MapBuilder {
+            map: Map::new(width, height),
+            rooms: Vec::new(),
+            monster_spawns: Vec::new(),
+            guard_posts: Vec::new(),
+            item_spawns: Vec::new(),
+            trap_spawns: Vec::new(),
+            secret_doors: Vec::new(),
+            boss_spawns: Vec::new(),
+            player_start: Point::zero(),
+            amulet_start: Point::zero(),
+            theme: super::themes::DungeonTheme::new(),
+        };
+        mb.fill(TileType::Wall);
+        self.split(Rect::with_size(1, 1, width - 2, height - 2), rng, &mut mb);
+        mb.add_doors();
+        mb.player_start = mb.rooms[0].center();
+        mb.amulet_start = mb.find_most_distant();
+
+        for room in mb.rooms.iter().skip(1) {
+            mb.monster_spawns.push(room.center());
+        }
+        mb.hide_doors(rng);
+        mb.add_traps(rng);
+
+        mb
+    }
+}
+
+impl BspArchitect {
+    // cuts `leaf` in two along its longer side until it is small enough for a single room,
+    // then joins the two halves; returns a point inside the leaf for its parent to connect to
+    fn split(&mut self, leaf: Rect, rng: &mut RandomNumberGenerator, mb: &mut MapBuilder) -> Point {
+        let (width, height) = (leaf.width(), leaf.height());
+        let vertical_cut = if width == height {
+            rng.range(0, 2) == 0
+        } else {
+            width > height
+        };
+        let length = if vertical_cut { width } else { height };
+        if length <= MAX_LEAF_SIZE || length < MIN_LEAF_SIZE * 2 {
+            return self.add_room(leaf, rng, mb);
+        }
+
+        let cut = rng.range(MIN_LEAF_SIZE, length - MIN_LEAF_SIZE + 1);
+        let (first, second) = if vertical_cut {
+            (
+                Rect::with_size(leaf.x1, leaf.y1, cut, height),
+                Rect::with_size(leaf.x1 + cut, leaf.y1, width - cut, height),
+            )
+        } else {
+            (
+                Rect::with_size(leaf.x1, leaf.y1, width, cut),
+                Rect::with_size(leaf.x1, leaf.y1 + cut, width, height - cut),
+            )
+        };
+        let a = self.split(first, rng, mb);
+        let b = self.split(second, rng, mb);
+        if rng.range(0, 2) == 1 {
+            mb.apply_horizontal_tunnel(a.x, b.x, a.y);
+            mb.apply_vertical_tunnel(a.y, b.y, b.x);
+        } else {
+            mb.apply_vertical_tunnel(a.y, b.y, a.x);
+            mb.apply_horizontal_tunnel(a.x, b.x, b.y);
+        }
+        a
+    }
+
+    // a randomly sized room somewhere inside the leaf, leaving a wall on every side
+    fn add_room(
+        &mut self,
+        leaf: Rect,
+        rng: &mut RandomNumberGenerator,
+        mb: &mut MapBuilder,
+    ) -> Point {
+        let width = rng.range(
+            MIN_ROOM_WIDTH,
+            i32::min(MAX_ROOM_WIDTH, leaf.width() - 2) + 1,
+        );
+        let height = rng.range(
+            MIN_ROOM_HEIGHT,
+            i32::min(MAX_ROOM_HEIGHT, leaf.height() - 2) + 1,
+        );
+        let room = Rect::with_size(
+            leaf.x1 + rng.range(1, leaf.width() - width),
+            leaf.y1 + rng.range(1, leaf.height() - height),
+            width,
+            height,
+        );
+        room.for_each(|p| {
+            if let Some(idx) = mb.map.try_idx(p) {
+                mb.map.tiles[idx] = TileType::Floor;
+            }
+        });
+        mb.rooms.push(room);
+        room.center()
+    }
+}
@@ -1,25 +1,30 @@
-use super::MapArchitect;
+use super::{MapArchitect, NUM_MONSTERS};
 use crate::prelude::*;
 
 const STAGGER_DISTANCE: usize = 400;
-const NUM_TILES: usize = (SCREEN_WIDTH * SCREEN_HEIGHT) as usize;
-const DESIRED_FLOOR: usize = NUM_TILES / 3;
+// dig until this share of the map is floor
+const DESIRED_FLOOR_DIVISOR: usize = 3;
 
 pub struct DrunkardsWalkArchitect {}
 
 impl MapArchitect for DrunkardsWalkArchitect {
-    fn new(&mut self, rng: &mut RandomNumberGenerator) -> MapBuilder {
+    fn new(&mut self, rng: &mut RandomNumberGenerator, width: i32, height: i32) -> MapBuilder {
         let mut mb = MapBuilder {
-            map: Map::new(),
+            map: Map::new(width, height),
             rooms: Vec::new(),
             monster_spawns: Vec::new(),
+            guard_posts: Vec::new(),
+            item_spawns: Vec::new(),
+            trap_spawns: Vec::new(),
+            secret_doors: Vec::new(),
+            boss_spawns: Vec::new(),
             player_start: Point::zero(),
             amulet_start: Point::zero(),
             theme: super::themes::DungeonTheme::new(),
         };
 
         mb.fill(TileType::Wall);
-        let center = Point::new(SCREEN_WIDTH / 2, SCREEN_HEIGHT / 2);
+        let center = Point::new(width / 2, height / 2);
         self.drunkard(&center, rng, &mut mb.map);
 
         while mb
@@ -28,20 +33,14 @@ impl MapArchitect for DrunkardsWalkArchitect {
             .iter()
             .filter(|t| **t == TileType::Floor)
             .count()
-            < DESIRED_FLOOR
+            < mb.map.tiles.len() / DESIRED_FLOOR_DIVISOR
         {
             self.drunkard(
-                &Point::new(rng.range(0, SCREEN_WIDTH), rng.range(0, SCREEN_HEIGHT)),
+                &Point::new(rng.range(0, width), rng.range(0, height)),
                 rng,
                 &mut mb.map,
             );
-            let dijkstra_map = DijkstraMap::new(
-                SCREEN_WIDTH,
-                SCREEN_HEIGHT,
-                &vec![mb.map.point2d_to_index(center)],
-                &mb.map,
-                1024.0,
-            );
+            let dijkstra_map = mb.map.distances(&[mb.map.point2d_to_index(center)]);
             dijkstra_map
                 .map
                 .iter()
@@ -49,9 +48,10 @@ impl MapArchitect for DrunkardsWalkArchitect {
                 .filter(|(_, distance)| *distance > &2000.0)
                 .for_each(|(idx, _)| mb.map.tiles[idx] = TileType::Wall);
         }
-        mb.monster_spawns = mb.spawn_monsters(&center, rng);
         mb.player_start = center;
         mb.amulet_start = mb.find_most_distant();
+        mb.monster_spawns = mb.spawn_monsters(&center, rng, mb.scaled(NUM_MONSTERS));
+        mb.add_traps(rng);
         mb
     }
 }
@@ -4,23 +4,26 @@ use crate::prelude::*;
 pub struct EmptyArchitect {}
 
 impl MapArchitect for EmptyArchitect {
-    fn new(&mut self, rng: &mut RandomNumberGenerator) -> MapBuilder {
+    fn new(&mut self, rng: &mut RandomNumberGenerator, width: i32, height: i32) -> MapBuilder {
         let mut mb = MapBuilder {
-            map: Map::new(),
+            map: Map::new(width, height),
             rooms: Vec::new(),
             monster_spawns: Vec::new(),
+            guard_posts: Vec::new(),
+            item_spawns: Vec::new(),
+            trap_spawns: Vec::new(),
+            secret_doors: Vec::new(),
+            boss_spawns: Vec::new(),
             player_start: Point::zero(),
             amulet_start: Point::zero(),
             theme: super::themes::DungeonTheme::new(),
         };
         mb.fill(TileType::Floor);
-        mb.player_start = Point::new(SCREEN_WIDTH / 2, SCREEN_HEIGHT / 2);
+        mb.player_start = Point::new(width / 2, height / 2);
         mb.amulet_start = mb.find_most_distant();
         for _ in 0..50 {
-            mb.monster_spawns.push(Point::new(
-                rng.range(1, SCREEN_WIDTH),
-                rng.range(1, SCREEN_HEIGHT),
-            ))
+            mb.monster_spawns
+                .push(Point::new(rng.range(1, width), rng.range(1, height)))
         }
         mb
     }
@@ -1,53 +1,93 @@
 mod automata;
+mod bsp;
 mod drunkard;
 mod empty;
 mod prefab;
 mod rooms;
+mod terrain;
 mod themes;
 use crate::prelude::*;
 use automata::CellularAutomataArchitect;
+use bsp::BspArchitect;
 use drunkard::DrunkardsWalkArchitect;
 use prefab::apply_prefab;
+pub use prefab::{Vault, Vaults};
 use rooms::RoomsArchitect;
+use terrain::apply_terrain;
 pub use themes::*;
 
+// room and monster counts are for a screen-sized map and scale with its area
 const NUM_ROOMS: usize = 20;
+const NUM_MONSTERS: usize = 50;
+const NUM_TRAPS: usize = 6;
+// traps keep this far from where the player starts
+const TRAP_CLEARANCE: f32 = 6.0;
+// one door in this many is hidden behind what looks like wall
+const SECRET_DOOR_CHANCE: i32 = 6;
+// give up on packing in more rooms after this many misses
+const MAX_ROOM_ATTEMPTS: usize = 2000;
 const MIN_ROOM_HEIGHT: i32 = 2;
 const MAX_ROOM_HEIGHT: i32 = 10;
 const MIN_ROOM_WIDTH: i32 = 2;
 const MAX_ROOM_WIDTH: i32 = 10;
 
 trait MapArchitect {
-    fn new(&mut self, rng: &mut RandomNumberGenerator) -> MapBuilder;
+    fn new(&mut self, rng: &mut RandomNumberGenerator, width: i32, height: i32) -> MapBuilder;
 }
 
 pub trait MapTheme: Send + Sync {
     fn tile_to_render(&self, tile_type: TileType) -> FontCharType;
+    // the font only has a few terrain tiles, so the rest are told apart by colour
+    fn tile_tint(&self, tile_type: TileType) -> (u8, u8, u8);
+    fn kind(&self) -> ThemeKind;
 }
 
 pub struct MapBuilder {
     pub map: Map,
     pub rooms: Vec<Rect>,
     pub monster_spawns: Vec<Point>,
+    // spawn points whose monsters stand guard instead of roaming
+    pub guard_posts: Vec<Point>,
+    // spots set aside for an item, such as vault treasure
+    pub item_spawns: Vec<Point>,
+    pub trap_spawns: Vec<Point>,
+    // walls that hide a closed door until the player finds it
+    pub secret_doors: Vec<Point>,
+    // where a vault wants the level's bosses to wait
+    pub boss_spawns: Vec<Point>,
     pub player_start: Point,
     pub amulet_start: Point,
     pub theme: Box<dyn MapTheme>,
 }
 
 impl MapBuilder {
-    pub fn new(rng: &mut RandomNumberGenerator) -> Self {
-        let mut architect: Box<dyn MapArchitect> = match rng.range(0, 3) {
-            0 => Box::new(DrunkardsWalkArchitect {}),
-            1 => Box::new(RoomsArchitect {}),
-            _ => Box::new(CellularAutomataArchitect {}),
+    pub fn new(
+        rng: &mut RandomNumberGenerator,
+        progression: &Progression,
+        vaults: &Vaults,
+        map_level: u32,
+    ) -> Self {
+        let plan = progression.level(map_level);
+        let mut architect: Box<dyn MapArchitect> = match rng
+            .random_slice_entry(&plan.architects)
+            .expect("Level has no architects")
+        {
+            ArchitectKind::Drunkard => Box::new(DrunkardsWalkArchitect {}),
+            ArchitectKind::Rooms => Box::new(RoomsArchitect {}),
+            ArchitectKind::Bsp => Box::new(BspArchitect {}),
+            ArchitectKind::Caves => Box::new(CellularAutomataArchitect {}),
         };
-        let mut mb = architect.new(rng);
-        apply_prefab(&mut mb, rng);
+        let (width, height) = plan.size(map_level);
+        let mut mb = architect.new(rng, width, height);
+        mb.fit_spawns(rng, plan.spawns);
+        // there is no level below the amulet to fall into
+        apply_terrain(&mut mb, rng, map_level < progression.amulet_level());
+        apply_prefab(&mut mb, rng, vaults, map_level as usize, &plan.vaults);
 
-        mb.theme = match rng.range(0, 2) {
-            0 => DungeonTheme::new(),
-            _ => ForestTheme::new(),
-        };
+        mb.theme = rng
+            .random_slice_entry(&plan.themes)
+            .expect("Level has no themes")
+            .build();
         mb
     }
 
@@ -56,32 +96,40 @@ impl MapBuilder {
     }
 
     fn find_most_distant(&self) -> Point {
-        let dijkstra_map = DijkstraMap::new(
-            SCREEN_WIDTH,
-            SCREEN_HEIGHT,
-            &vec![self.map.point2d_to_index(self.player_start)],
-            &self.map,
-            1024.0,
-        );
+        let start = self.map.point2d_to_index(self.player_start);
+        let dijkstra_map = self.map.distances(&[start]);
 
+        // the start only gets a distance by stepping out and back, which in a small room can
+        // be as far as anywhere else
         const UNREACHABLE: &f32 = &f32::MAX;
         self.map.index_to_point2d(
             dijkstra_map
                 .map
                 .iter()
                 .enumerate()
-                .filter(|(_, dist)| *dist < UNREACHABLE)
+                .filter(|(idx, dist)| *idx != start && *dist < UNREACHABLE)
                 .max_by(|a, b| a.1.partial_cmp(b.1).unwrap())
-                .unwrap()
-                .0,
+                .map_or(start, |(idx, _)| idx),
+        )
+    }
+
+    // how many of something a map of this size should get
+    fn scaled(&self, count: usize) -> usize {
+        usize::max(
+            1,
+            count * self.map.tiles.len() / (SCREEN_WIDTH * SCREEN_HEIGHT) as usize,
         )
     }
 
     fn build_random_rooms(&mut self, rng: &mut RandomNumberGenerator) {
-        while self.rooms.len() < NUM_ROOMS {
+        // however small the map, the exit wants a room of its own
+        let num_rooms = usize::max(2, self.scaled(NUM_ROOMS));
+        let mut attempts = 0;
+        while self.rooms.len() < num_rooms && attempts < MAX_ROOM_ATTEMPTS {
+            attempts += 1;
             let room = Rect::with_size(
-                rng.range(1, SCREEN_WIDTH - 10),
-                rng.range(1, SCREEN_HEIGHT - 10),
+                rng.range(1, i32::max(2, self.map.width - 10)),
+                rng.range(1, i32::max(2, self.map.height - 10)),
                 rng.range(MIN_ROOM_WIDTH, MAX_ROOM_WIDTH),
                 rng.range(MIN_ROOM_HEIGHT, MAX_ROOM_HEIGHT),
             );
@@ -93,8 +141,8 @@ impl MapBuilder {
             }
             if !overlap {
                 room.for_each(|p| {
-                    if p.x > 0 && p.x < SCREEN_WIDTH && p.y > 0 && p.y < SCREEN_HEIGHT {
-                        let idx = map_idx(p.x, p.y);
+                    if p.x > 0 && p.x < self.map.width && p.y > 0 && p.y < self.map.height {
+                        let idx = self.map.point2d_to_index(p);
                         self.map.tiles[idx] = TileType::Floor;
                     }
                 });
@@ -140,8 +188,83 @@ impl MapBuilder {
         }
     }
 
-    fn spawn_monsters(&self, start: &Point, rng: &mut RandomNumberGenerator) -> Vec<Point> {
-        const NUM_MONSTERS: usize = 50;
+    // hangs a door in every one-tile gap where a corridor runs into a room
+    fn add_doors(&mut self) {
+        let mut gaps = Vec::new();
+        for room in self.rooms.iter() {
+            for x in room.x1..room.x2 {
+                gaps.push((Point::new(x, room.y1 - 1), Point::new(1, 0)));
+                gaps.push((Point::new(x, room.y2), Point::new(1, 0)));
+            }
+            for y in room.y1..room.y2 {
+                gaps.push((Point::new(room.x1 - 1, y), Point::new(0, 1)));
+                gaps.push((Point::new(room.x2, y), Point::new(0, 1)));
+            }
+        }
+        let tile = |pt: Point| self.map.try_idx(pt).map(|idx| self.map.tiles[idx]);
+        let doors: Vec<usize> = gaps
+            .iter()
+            .filter(|(pt, along)| {
+                tile(*pt) == Some(TileType::Floor)
+                    && tile(*pt - *along) == Some(TileType::Wall)
+                    && tile(*pt + *along) == Some(TileType::Wall)
+                    && !self.rooms.iter().any(|room| room.point_in_rect(*pt))
+            })
+            .map(|(pt, _)| self.map.point2d_to_index(*pt))
+            .collect();
+        doors
+            .iter()
+            .for_each(|idx| self.map.tiles[*idx] = TileType::Door { open: false });
+    }
+
+    // turns some doors into secret ones, as long as the exit stays reachable without them
+    fn hide_doors(&mut self, rng: &mut RandomNumberGenerator) {
+        let start = self.map.point2d_to_index(self.player_start);
+        let exit = self.map.point2d_to_index(self.amulet_start);
+        for idx in 0..self.map.tiles.len() {
+            if !matches!(self.map.tiles[idx], TileType::Door { .. })
+                || rng.range(0, SECRET_DOOR_CHANCE) != 0
+            {
+                continue;
+            }
+            self.map.tiles[idx] = TileType::Wall;
+            if self.map.distances(&[start]).map[exit] == f32::MAX {
+                self.map.tiles[idx] = TileType::Door { open: false };
+            } else {
+                self.secret_doors.push(self.map.index_to_point2d(idx));
+            }
+        }
+    }
+
+    fn add_traps(&mut self, rng: &mut RandomNumberGenerator) {
+        let mut trappable_tiles: Vec<Point> = self
+            .map
+            .tiles
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| **t == TileType::Floor)
+            .map(|(idx, _)| self.map.index_to_point2d(idx))
+            .filter(|pt| {
+                DistanceAlg::Pythagoras.distance2d(*pt, self.player_start) > TRAP_CLEARANCE
+                    && *pt != self.amulet_start
+                    && !self.monster_spawns.contains(pt)
+                    && !self.item_spawns.contains(pt)
+            })
+            .collect();
+
+        for _ in 0..usize::min(self.scaled(NUM_TRAPS), trappable_tiles.len()) {
+            let target_index = rng.random_slice_index(&trappable_tiles).unwrap();
+            self.trap_spawns.push(trappable_tiles[target_index]);
+            trappable_tiles.remove(target_index);
+        }
+    }
+
+    fn spawn_monsters(
+        &self,
+        start: &Point,
+        rng: &mut RandomNumberGenerator,
+        count: usize,
+    ) -> Vec<Point> {
         let mut spawnable_tiles: Vec<Point> = self
             .map
             .tiles
@@ -153,14 +276,33 @@ impl MapBuilder {
                         > 10.0
             })
             .map(|(idx, _)| self.map.index_to_point2d(idx))
+            .filter(|pt| {
+                *pt != self.amulet_start
+                    && !self.monster_spawns.contains(pt)
+                    && !self.trap_spawns.contains(pt)
+                    && !self.item_spawns.contains(pt)
+                    && !self.boss_spawns.contains(pt)
+            })
             .collect();
 
         let mut spawns = Vec::new();
-        for _ in 0..NUM_MONSTERS {
+        for _ in 0..usize::min(count, spawnable_tiles.len()) {
             let target_index = rng.random_slice_index(&spawnable_tiles).unwrap();
             spawns.push(spawnable_tiles[target_index].clone());
             spawnable_tiles.remove(target_index);
         }
         spawns
     }
+
+    // trims or tops up the architect's spawn points to what the level asks for
+    fn fit_spawns(&mut self, rng: &mut RandomNumberGenerator, count: usize) {
+        let wanted = if count == 0 { 0 } else { self.scaled(count) };
+        while self.monster_spawns.len() > wanted {
+            let idx = rng.random_slice_index(&self.monster_spawns).unwrap();
+            self.monster_spawns.remove(idx);
+        }
+        let missing = wanted - self.monster_spawns.len();
+        let mut extra = self.spawn_monsters(&self.player_start.clone(), rng, missing);
+        self.monster_spawns.append(&mut extra);
+    }
 }
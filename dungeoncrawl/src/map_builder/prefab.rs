@@ -1,79 +1,289 @@
 use crate::prelude::*;
+use ron::de::from_reader;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
 
-const FORTRESS: (&str, i32, i32) = (
-    "
-------------
----######---
----#----#---
----#-M--#---
--###----###-
---M------M--
--###----###-
----#----#---
----#----#---
----######---
-------------
-",
-    12,
-    11,
-);
-
-pub fn apply_prefab(mb: &mut MapBuilder, rng: &mut RandomNumberGenerator) {
-    let mut placement = None;
-
-    let dijkstra_map = DijkstraMap::new(
-        SCREEN_WIDTH,
-        SCREEN_HEIGHT,
-        &vec![mb.map.point2d_to_index(mb.player_start)],
-        &mb.map,
-        1024.0,
-    );
-
-    let mut attempts = 0;
-    while placement.is_none() && attempts < 10 {
+// vault counts are for a screen-sized map and scale with its area
+const MAX_VAULTS: usize = 2;
+const PLACEMENT_ATTEMPTS: usize = 50;
+
+// what a character in a vault layout turns into
+#[derive(Clone, Copy, Deserialize, Debug, PartialEq)]
+pub enum VaultTile {
+    Floor,
+    Wall,
+    // a closed door in the vault's walls
+    Door,
+    Monster,
+    // a monster that holds its ground instead of roaming
+    Guard,
+    Item,
+    // a hidden trap on open floor
+    Trap,
+    // looks like wall until the player finds it
+    SecretDoor,
+    // the level plan's boss waits here, or a monster if it has none
+    Boss,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct Vault {
+    pub name: String,
+    // where it may turn up at random; a level plan can ask for it anywhere
+    #[serde(default)]
+    pub levels: HashSet<usize>,
+    pub frequency: i32,
+    // one row per line; the vault is as wide as its rows and as tall as their count
+    pub layout: String,
+    // extra characters on top of the default legend, or replacements for it
+    #[serde(default)]
+    pub legend: HashMap<char, VaultTile>,
+    // whether it may be turned a random number of quarter turns
+    #[serde(default)]
+    pub rotate: bool,
+    // whether it may be flipped left to right
+    #[serde(default)]
+    pub mirror: bool,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct Vaults {
+    pub vaults: Vec<Vault>,
+}
+
+impl Vaults {
+    pub fn load() -> Self {
+        let file = File::open("resources/vaults.ron").expect("Failed opening file");
+        let vaults: Self = from_reader(file).expect("Unable to load vaults");
+        if let Err(problem) = vaults.validate() {
+            panic!("Invalid vaults: {}", problem);
+        }
+        vaults
+    }
+
+    // catches bad layouts in vaults.ron up front instead of when a level is built with them
+    pub fn validate(&self) -> Result<(), String> {
+        self.vaults
+            .iter()
+            .try_for_each(|vault| vault.grid().map(|_| ()))
+    }
+
+    pub fn named(&self, name: &str) -> Option<&Vault> {
+        self.vaults.iter().find(|v| v.name == name)
+    }
+}
+
+impl Vault {
+    fn tile(&self, c: char) -> Result<VaultTile, String> {
+        if let Some(tile) = self.legend.get(&c) {
+            return Ok(*tile);
+        }
+        match c {
+            '-' => Ok(VaultTile::Floor),
+            '#' => Ok(VaultTile::Wall),
+            '+' => Ok(VaultTile::Door),
+            'm' => Ok(VaultTile::Monster),
+            'M' => Ok(VaultTile::Guard),
+            '!' => Ok(VaultTile::Item),
+            '^' => Ok(VaultTile::Trap),
+            'S' => Ok(VaultTile::SecretDoor),
+            'B' => Ok(VaultTile::Boss),
+            _ => Err(format!("{} has no legend entry for [{}]", self.name, c)),
+        }
+    }
+
+    // the layout as rows of tiles, as written in the file
+    fn grid(&self) -> Result<Vec<Vec<VaultTile>>, String> {
+        let grid: Vec<Vec<VaultTile>> = self
+            .layout
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| line.chars().map(|c| self.tile(c)).collect())
+            .collect::<Result<_, _>>()?;
+        if grid.is_empty() {
+            return Err(format!("{} has an empty layout", self.name));
+        }
+        if grid.iter().any(|row| row.len() != grid[0].len()) {
+            return Err(format!("{} has rows of different lengths", self.name));
+        }
+        Ok(grid)
+    }
+
+    // whether a map this size has room for it, whichever way it is turned
+    pub fn fits(&self, width: i32, height: i32) -> bool {
+        let grid = self.grid().expect("Vaults are checked when they load");
+        let (vault_width, vault_height) = (grid[0].len() as i32, grid.len() as i32);
+        has_room(width, height, vault_width, vault_height)
+            && (!self.rotate || has_room(width, height, vault_height, vault_width))
+    }
+
+    // the layout as rows of tiles, randomly turned and flipped if the vault allows it
+    fn arrange(&self, rng: &mut RandomNumberGenerator) -> Vec<Vec<VaultTile>> {
+        let mut grid = self.grid().expect("Vaults are checked when they load");
+
+        if self.rotate {
+            for _ in 0..rng.range(0, 4) {
+                let height = grid.len();
+                grid = (0..grid[0].len())
+                    .map(|x| (0..height).map(|y| grid[height - 1 - y][x]).collect())
+                    .collect();
+            }
+        }
+        if self.mirror && rng.range(0, 2) == 1 {
+            grid.iter_mut().for_each(|row| row.reverse());
+        }
+        grid
+    }
+}
+
+pub fn apply_prefab(
+    mb: &mut MapBuilder,
+    rng: &mut RandomNumberGenerator,
+    vaults: &Vaults,
+    level: usize,
+    guaranteed: &[String],
+) {
+    let mut placed = Vec::new();
+    // the level plan's own vaults go in first, while there is the most room for them
+    for name in guaranteed {
+        let vault = vaults
+            .named(name)
+            .unwrap_or_else(|| panic!("No vault named {}", name));
+        let grid = vault.arrange(rng);
+        if let Some(dimensions) = place_vault(mb, rng, &grid, &placed) {
+            placed.push(dimensions);
+        }
+    }
+
+    let mut available_vaults = Vec::new();
+    vaults
+        .vaults
+        .iter()
+        .filter(|v| v.levels.contains(&level))
+        .for_each(|v| {
+            for _ in 0..v.frequency {
+                available_vaults.push(v);
+            }
+        });
+    if available_vaults.is_empty() {
+        return;
+    }
+
+    let wanted = rng.range(0, mb.scaled(MAX_VAULTS) as i32) + 1;
+    for _ in 0..wanted {
+        let vault = rng.random_slice_entry(&available_vaults).unwrap();
+        let grid = vault.arrange(rng);
+        if let Some(dimensions) = place_vault(mb, rng, &grid, &placed) {
+            placed.push(dimensions);
+        }
+    }
+}
+
+// stamps the vault somewhere reachable that keeps clear of the player, the exit and other vaults
+fn place_vault(
+    mb: &mut MapBuilder,
+    rng: &mut RandomNumberGenerator,
+    grid: &[Vec<VaultTile>],
+    placed: &[Rect],
+) -> Option<Rect> {
+    let (width, height) = (grid[0].len() as i32, grid.len() as i32);
+
+    // small maps have no room for it
+    if !has_room(mb.map.width, mb.map.height, width, height) {
+        return None;
+    }
+
+    let dijkstra_map = mb
+        .map
+        .distances(&[mb.map.point2d_to_index(mb.player_start)]);
+
+    for _ in 0..PLACEMENT_ATTEMPTS {
         let dimensions = Rect::with_size(
-            rng.range(0, SCREEN_WIDTH - FORTRESS.1),
-            rng.range(0, SCREEN_HEIGHT - FORTRESS.2),
-            FORTRESS.1,
-            FORTRESS.2,
+            rng.range(1, mb.map.width - width - 1),
+            rng.range(1, mb.map.height - height - 1),
+            width,
+            height,
         );
         let mut can_place = false;
         dimensions.for_each(|pt| {
-            let idx = mb.map.point2d_to_index(pt);
-            let distance = dijkstra_map.map[idx];
-            if distance < 2000.0 && distance > 20.0 && mb.amulet_start != pt {
+            let distance = dijkstra_map.map[mb.map.point2d_to_index(pt)];
+            if distance < 2000.0 && distance > 20.0 {
                 can_place = true;
             }
         });
-        if can_place {
-            placement = Some(Point::new(dimensions.x1, dimensions.y1));
-            let points = dimensions.point_set();
-            mb.monster_spawns.retain(|pt| !points.contains(pt));
+        if !can_place
+            || dimensions.point_in_rect(mb.player_start)
+            || dimensions.point_in_rect(mb.amulet_start)
+            || placed.iter().any(|r| r.intersect(&dimensions))
+        {
+            continue;
+        }
+
+        let tiles = mb.map.tiles.clone();
+        let monster_spawns = mb.monster_spawns.clone();
+        let guard_posts = mb.guard_posts.clone();
+        let item_spawns = mb.item_spawns.clone();
+        let trap_spawns = mb.trap_spawns.clone();
+        let secret_doors = mb.secret_doors.clone();
+        let boss_spawns = mb.boss_spawns.clone();
+        stamp(mb, grid, &dimensions);
+
+        // a vault's walls must not cut the player off from the exit
+        let start = mb.map.point2d_to_index(mb.player_start);
+        let exit = mb.map.point2d_to_index(mb.amulet_start);
+        if mb.map.distances(&[start]).map[exit] < 2000.0 {
+            return Some(dimensions);
         }
-        attempts += 1;
+        mb.map.tiles = tiles;
+        mb.monster_spawns = monster_spawns;
+        mb.guard_posts = guard_posts;
+        mb.item_spawns = item_spawns;
+        mb.trap_spawns = trap_spawns;
+        mb.secret_doors = secret_doors;
+        mb.boss_spawns = boss_spawns;
     }
+    None
+}
+
+// vaults keep more than a tile clear of the map's edge on every side
+fn has_room(map_width: i32, map_height: i32, width: i32, height: i32) -> bool {
+    map_width - 2 > width && map_height - 2 > height
+}
 
-    if let Some(placement) = placement {
-        let string_vec: Vec<char> = FORTRESS
-            .0
-            .chars()
-            .filter(|a| *a != '\r' && *a != '\n')
-            .collect();
-        let mut i = 0;
-        for ty in placement.y..placement.y + FORTRESS.2 {
-            for tx in placement.x..placement.x + FORTRESS.1 {
-                let idx = map_idx(tx, ty);
-                let c = string_vec[i];
-                match c {
-                    'M' => {
-                        mb.map.tiles[idx] = TileType::Floor;
-                        mb.monster_spawns.push(Point::new(tx, ty));
-                    }
-                    '-' => mb.map.tiles[idx] = TileType::Floor,
-                    '#' => mb.map.tiles[idx] = TileType::Wall,
-                    _ => println!("No idea what to do with [{}]", c),
+fn stamp(mb: &mut MapBuilder, grid: &[Vec<VaultTile>], dimensions: &Rect) {
+    let points = dimensions.point_set();
+    mb.monster_spawns.retain(|pt| !points.contains(pt));
+    mb.guard_posts.retain(|pt| !points.contains(pt));
+    mb.item_spawns.retain(|pt| !points.contains(pt));
+    mb.trap_spawns.retain(|pt| !points.contains(pt));
+    mb.secret_doors.retain(|pt| !points.contains(pt));
+    mb.boss_spawns.retain(|pt| !points.contains(pt));
+
+    for (y, row) in grid.iter().enumerate() {
+        for (x, tile) in row.iter().enumerate() {
+            let pt = Point::new(dimensions.x1 + x as i32, dimensions.y1 + y as i32);
+            let idx = mb.map.point2d_to_index(pt);
+            mb.map.tiles[idx] = match tile {
+                VaultTile::Wall | VaultTile::SecretDoor => TileType::Wall,
+                VaultTile::Door => TileType::Door { open: false },
+                _ => TileType::Floor,
+            };
+            match tile {
+                VaultTile::Monster => mb.monster_spawns.push(pt),
+                VaultTile::Guard => {
+                    mb.monster_spawns.push(pt);
+                    mb.guard_posts.push(pt);
+                }
+                VaultTile::Item => mb.item_spawns.push(pt),
+                VaultTile::Trap => mb.trap_spawns.push(pt),
+                VaultTile::SecretDoor => mb.secret_doors.push(pt),
+                VaultTile::Boss => {
+                    mb.monster_spawns.push(pt);
+                    mb.boss_spawns.push(pt);
                 }
-                i += 1;
+                _ => {}
             }
         }
     }
@@ -4,11 +4,16 @@ use crate::prelude::*;
 pub struct RoomsArchitect {}
 
 impl MapArchitect for RoomsArchitect {
-    fn new(&mut self, rng: &mut RandomNumberGenerator) -> MapBuilder {
+    fn new(&mut self, rng: &mut RandomNumberGenerator, width: i32, height: i32) -> MapBuilder {
         let mut mb = MapBuilder {
-            map: Map::new(),
+            map: Map::new(width, height),
             rooms: Vec::new(),
             monster_spawns: Vec::new(),
+            guard_posts: Vec::new(),
+            item_spawns: Vec::new(),
+            trap_spawns: Vec::new(),
+            secret_doors: Vec::new(),
+            boss_spawns: Vec::new(),
             player_start: Point::zero(),
             amulet_start: Point::zero(),
             theme: super::themes::DungeonTheme::new(),
@@ -16,12 +21,15 @@ impl MapArchitect for RoomsArchitect {
         mb.fill(TileType::Wall);
         mb.build_random_rooms(rng);
         mb.build_corridors(rng);
+        mb.add_doors();
         mb.player_start = mb.rooms[0].center();
         mb.amulet_start = mb.find_most_distant();
 
         for room in mb.rooms.iter().skip(1) {
             mb.monster_spawns.push(room.center());
         }
+        mb.hide_doors(rng);
+        mb.add_traps(rng);
 
         mb
     }
@@ -0,0 +1,83 @@
+use crate::prelude::*;
+
+// patch counts are for a screen-sized map and scale with its area
+const NUM_POOLS: usize = 6;
+const NUM_RUBBLE: usize = 4;
+// how many steps the digger takes for each patch
+const PATCH_STEPS: usize = 16;
+// patches keep this far from where the player starts and from the exit
+const CLEARANCE: f32 = 4.0;
+
+pub fn apply_terrain(mb: &mut MapBuilder, rng: &mut RandomNumberGenerator, chasms: bool) {
+    let mut pools = vec![TileType::DeepWater, TileType::DeepWater, TileType::Lava];
+    if chasms {
+        pools.push(TileType::Chasm);
+    }
+
+    for _ in 0..mb.scaled(NUM_POOLS) {
+        let tile = *rng.random_slice_entry(&pools).unwrap();
+        let tiles = mb.map.tiles.clone();
+        spread(mb, rng, tile);
+        // a pool must never cut the player off from the exit
+        let start = mb.map.point2d_to_index(mb.player_start);
+        let exit = mb.map.point2d_to_index(mb.amulet_start);
+        if mb.map.distances(&[start]).map[exit] == f32::MAX {
+            mb.map.tiles = tiles;
+        }
+    }
+    for _ in 0..mb.scaled(NUM_RUBBLE) {
+        spread(mb, rng, TileType::Rubble);
+    }
+}
+
+// a short drunkard's walk over open floor, leaving `tile` behind it
+fn spread(mb: &mut MapBuilder, rng: &mut RandomNumberGenerator, tile: TileType) {
+    let reserved: Vec<Point> = mb
+        .monster_spawns
+        .iter()
+        .chain(mb.item_spawns.iter())
+        .chain(mb.trap_spawns.iter())
+        .cloned()
+        .collect();
+    let clear = |pt: Point| {
+        DistanceAlg::Pythagoras.distance2d(pt, mb.player_start) > CLEARANCE
+            && DistanceAlg::Pythagoras.distance2d(pt, mb.amulet_start) > CLEARANCE
+            && !reserved.contains(&pt)
+    };
+    let floor: Vec<Point> = mb
+        .map
+        .tiles
+        .iter()
+        .enumerate()
+        .filter(|(_, t)| **t == TileType::Floor)
+        .map(|(idx, _)| mb.map.index_to_point2d(idx))
+        .filter(|pt| clear(*pt))
+        .collect();
+    let mut pos = match rng.random_slice_entry(&floor) {
+        Some(pos) => *pos,
+        None => return,
+    };
+
+    let mut changed = Vec::new();
+    for _ in 0..PATCH_STEPS {
+        if clear(pos) {
+            changed.push(mb.map.point2d_to_index(pos));
+        }
+        let next = pos
+            + match rng.range(0, 4) {
+                0 => Point::new(-1, 0),
+                1 => Point::new(1, 0),
+                2 => Point::new(0, -1),
+                _ => Point::new(0, 1),
+            };
+        // stay on open ground rather than tunnelling through walls
+        if mb
+            .map
+            .try_idx(next)
+            .is_some_and(|idx| mb.map.tiles[idx] == TileType::Floor)
+        {
+            pos = next;
+        }
+    }
+    changed.iter().for_each(|idx| mb.map.tiles[*idx] = tile);
+}
@@ -1,8 +1,24 @@
 use crate::prelude::*;
+use serde::{Deserialize, Serialize};
 
 pub struct DungeonTheme {}
 pub struct ForestTheme {}
 
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ThemeKind {
+    Dungeon,
+    Forest,
+}
+
+impl ThemeKind {
+    pub fn build(&self) -> Box<dyn MapTheme> {
+        match self {
+            ThemeKind::Dungeon => DungeonTheme::new(),
+            ThemeKind::Forest => ForestTheme::new(),
+        }
+    }
+}
+
 impl DungeonTheme {
     pub fn new() -> Box<dyn MapTheme> {
         Box::new(Self {})
@@ -14,9 +30,30 @@ impl MapTheme for DungeonTheme {
         match tile_type {
             TileType::Floor => to_cp437('.'),
             TileType::Wall => to_cp437('#'),
-            TileType::Exit => to_cp437('>'),
+            // the font has no '<', so stairs up are a tinted '>'
+            TileType::Exit | TileType::UpStairs => to_cp437('>'),
+            TileType::Door { open: false } => to_cp437('#'),
+            TileType::Door { open: true } => to_cp437('.'),
+            TileType::DeepWater | TileType::Lava | TileType::Chasm => to_cp437('.'),
+            TileType::Rubble => to_cp437(';'),
+        }
+    }
+
+    fn tile_tint(&self, tile_type: TileType) -> (u8, u8, u8) {
+        match tile_type {
+            TileType::Door { .. } => BURLYWOOD,
+            TileType::DeepWater => DODGER_BLUE,
+            TileType::Lava => ORANGE_RED,
+            TileType::Chasm => (32, 32, 32),
+            TileType::Rubble => GRAY,
+            TileType::UpStairs => SKY_BLUE,
+            TileType::Floor | TileType::Wall | TileType::Exit => WHITE,
         }
     }
+
+    fn kind(&self) -> ThemeKind {
+        ThemeKind::Dungeon
+    }
 }
 
 impl ForestTheme {
@@ -30,7 +67,28 @@ impl MapTheme for ForestTheme {
         match tile_type {
             TileType::Floor => to_cp437(';'),
             TileType::Wall => to_cp437('"'),
-            TileType::Exit => to_cp437('>'),
+            // the font has no '<', so stairs up are a tinted '>'
+            TileType::Exit | TileType::UpStairs => to_cp437('>'),
+            TileType::Door { open: false } => to_cp437('"'),
+            TileType::Door { open: true } => to_cp437(';'),
+            TileType::DeepWater | TileType::Lava | TileType::Chasm => to_cp437(';'),
+            TileType::Rubble => to_cp437('.'),
         }
     }
+
+    fn tile_tint(&self, tile_type: TileType) -> (u8, u8, u8) {
+        match tile_type {
+            TileType::Door { .. } => BURLYWOOD,
+            TileType::DeepWater => STEEL_BLUE,
+            TileType::Lava => ORANGE_RED,
+            TileType::Chasm => (24, 24, 16),
+            TileType::Rubble => LIGHT_GRAY,
+            TileType::UpStairs => SKY_BLUE,
+            TileType::Floor | TileType::Wall | TileType::Exit => WHITE,
+        }
+    }
+
+    fn kind(&self) -> ThemeKind {
+        ThemeKind::Forest
+    }
 }
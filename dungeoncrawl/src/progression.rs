@@ -0,0 +1,117 @@
+use crate::prelude::*;
+use ron::de::from_reader;
+use serde::Deserialize;
+use std::fs::File;
+
+// how much wider and taller each level is than the one above, unless it sets its own size
+const LEVEL_GROWTH: (i32, i32) = (20, 10);
+// the smallest map every architect can still lay out; a level's own vaults may need more
+pub const MIN_LEVEL_SIZE: (i32, i32) = (10, 8);
+
+#[derive(Clone, Copy, Deserialize, Debug, PartialEq)]
+pub enum ArchitectKind {
+    Rooms,
+    Bsp,
+    Caves,
+    Drunkard,
+}
+
+// how one depth of the dungeon is built and what turns up on it
+#[derive(Clone, Deserialize, Debug)]
+pub struct LevelPlan {
+    pub name: String,
+    // one of each is picked at random
+    pub architects: Vec<ArchitectKind>,
+    pub themes: Vec<ThemeKind>,
+    // the monster and item templates that can spawn here, each at its own frequency
+    pub templates: Vec<String>,
+    // monsters and items for a screen-sized map; bigger maps get proportionally more
+    pub spawns: usize,
+    // vaults always placed here, on top of any picked at random
+    #[serde(default)]
+    pub vaults: Vec<String>,
+    // monsters always spawned here, as far from the way in as possible
+    #[serde(default)]
+    pub bosses: Vec<String>,
+    #[serde(default)]
+    pub size: Option<(i32, i32)>,
+}
+
+impl LevelPlan {
+    pub fn size(&self, map_level: u32) -> (i32, i32) {
+        self.size.unwrap_or((
+            SCREEN_WIDTH + LEVEL_GROWTH.0 * map_level as i32,
+            SCREEN_HEIGHT + LEVEL_GROWTH.1 * map_level as i32,
+        ))
+    }
+}
+
+// the dungeon from top to bottom; the amulet lies on the last level
+#[derive(Clone, Deserialize, Debug)]
+pub struct Progression {
+    pub levels: Vec<LevelPlan>,
+}
+
+impl Progression {
+    pub fn load(templates: &Templates, vaults: &Vaults) -> Self {
+        let file = File::open("resources/levels.ron").expect("Failed opening file");
+        let progression: Self = from_reader(file).expect("Unable to load level progression");
+        if let Err(problem) = progression.validate(templates, vaults) {
+            panic!("Invalid level progression: {}", problem);
+        }
+        progression
+    }
+
+    // catches mistakes in levels.ron up front instead of when a level is built from them
+    pub fn validate(&self, templates: &Templates, vaults: &Vaults) -> Result<(), String> {
+        if self.levels.is_empty() {
+            return Err("there are no levels".to_string());
+        }
+        for (map_level, plan) in self.levels.iter().enumerate() {
+            let (width, height) = plan.size(map_level as u32);
+            if plan.architects.is_empty() {
+                return Err(format!("{} has no architects", plan.name));
+            }
+            if plan.themes.is_empty() {
+                return Err(format!("{} has no themes", plan.name));
+            }
+            if width < MIN_LEVEL_SIZE.0 || height < MIN_LEVEL_SIZE.1 {
+                return Err(format!(
+                    "{} is {}x{}, smaller than the {}x{} minimum",
+                    plan.name, width, height, MIN_LEVEL_SIZE.0, MIN_LEVEL_SIZE.1
+                ));
+            }
+            // a misspelt name would otherwise only turn up once a run got this deep
+            if let Some(name) = plan
+                .templates
+                .iter()
+                .chain(plan.bosses.iter())
+                .find(|name| !templates.contains(name))
+            {
+                return Err(format!("{} has no template named {}", plan.name, name));
+            }
+            for name in plan.vaults.iter() {
+                match vaults.named(name) {
+                    None => return Err(format!("{} has no vault named {}", plan.name, name)),
+                    Some(vault) if !vault.fits(width, height) => {
+                        return Err(format!(
+                            "{} is {}x{}, too small for the {} vault",
+                            plan.name, width, height, name
+                        ))
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // there is nothing below the amulet's level, so anything deeper is built like it
+    pub fn level(&self, map_level: u32) -> &LevelPlan {
+        &self.levels[(map_level as usize).min(self.levels.len() - 1)]
+    }
+
+    pub fn amulet_level(&self) -> u32 {
+        self.levels.len() as u32 - 1
+    }
+}
@@ -0,0 +1,96 @@
+use crate::prelude::*;
+use ron::de::from_reader;
+use ron::ser::{to_string_pretty, PrettyConfig};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::Write;
+
+pub const REPLAY_FILE: &str = "replay.ron";
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ReplayInput {
+    pub turn: usize,
+    pub key: Option<String>,
+    pub mouse: (i32, i32),
+    #[serde(default)]
+    pub click: bool,
+}
+
+impl ReplayInput {
+    pub fn key(&self) -> Option<VirtualKeyCode> {
+        self.key
+            .as_ref()
+            .map(|key| parse_key(key).unwrap_or_else(|| panic!("Unknown key in replay: {}", key)))
+    }
+
+    pub fn mouse(&self) -> Point {
+        Point::new(self.mouse.0, self.mouse.1)
+    }
+}
+
+// every key press and mouse click handed to the input systems, in order, for one run
+#[derive(Serialize, Deserialize)]
+pub struct Replay {
+    pub seed: Seed,
+    #[serde(default)]
+    pub movement: Movement,
+    // the run was continued from this save rather than started fresh
+    pub save: Option<SaveGame>,
+    pub inputs: Vec<ReplayInput>,
+    pub outcome: Option<TurnState>,
+}
+
+impl Replay {
+    pub fn new(seed: Seed, movement: Movement) -> Self {
+        Self {
+            seed,
+            movement,
+            save: None,
+            inputs: Vec::new(),
+            outcome: None,
+        }
+    }
+
+    pub fn continued(save: SaveGame) -> Self {
+        Self {
+            seed: save.seed,
+            movement: save.map.movement,
+            save: Some(save),
+            inputs: Vec::new(),
+            outcome: None,
+        }
+    }
+
+    pub fn record(&mut self, key: Option<VirtualKeyCode>, mouse_pos: Point, clicked: bool) {
+        self.inputs.push(ReplayInput {
+            turn: self.inputs.len(),
+            key: key.map(key_name),
+            mouse: (mouse_pos.x, mouse_pos.y),
+            click: clicked,
+        });
+    }
+
+    pub fn write(&self, path: &str) {
+        let contents =
+            to_string_pretty(self, PrettyConfig::default()).expect("Unable to serialize replay");
+        let mut file = File::create(path).expect("Failed creating replay file");
+        file.write_all(contents.as_bytes())
+            .expect("Failed writing replay file");
+    }
+
+    // a replay from an older version or a damaged file is an error, not a crash
+    pub fn load(path: &str) -> Result<Self, String> {
+        let file = File::open(path).map_err(|err| format!("Failed opening {}: {}", path, err))?;
+        let replay: Self =
+            from_reader(file).map_err(|err| format!("Unable to load {}: {}", path, err))?;
+        if let Some(key) = replay
+            .inputs
+            .iter()
+            .filter_map(|input| input.key.as_ref())
+            .find(|key| parse_key(key).is_none())
+        {
+            return Err(format!("Unknown key in replay {}: {}", path, key));
+        }
+        Ok(replay)
+    }
+}
@@ -0,0 +1,29 @@
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+
+// the run's high points, shown once it is over
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct RunSummary {
+    pub bosses_slain: Vec<String>,
+}
+
+impl RunSummary {
+    // callers need read access to Boss and Name, and must call it before the victim is removed
+    pub fn record_kill(&mut self, ecs: &SubWorld, victim: Entity) {
+        if let Ok(entry) = ecs.entry_ref(victim) {
+            if entry.get_component::<Boss>().is_ok() {
+                if let Ok(name) = entry.get_component::<Name>() {
+                    self.bosses_slain.push(name.0.clone());
+                }
+            }
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        if self.bosses_slain.is_empty() {
+            "No bosses were slain.".to_string()
+        } else {
+            format!("Bosses slain: {}.", self.bosses_slain.join(", "))
+        }
+    }
+}
@@ -0,0 +1,429 @@
+use crate::prelude::*;
+use ron::de::from_reader;
+use ron::ser::{to_string_pretty, PrettyConfig};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{remove_file, File};
+use std::io::Write;
+use std::path::Path;
+
+pub const SAVE_FILE: &str = "savegame.ron";
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SavedRender {
+    pub fg: (f32, f32, f32, f32),
+    pub bg: (f32, f32, f32, f32),
+    pub glyph: FontCharType,
+}
+
+// one entry per entity; Carried and Equipped refer to their owner's index in SaveGame::entities
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct SavedEntity {
+    pub position: Option<(i32, i32)>,
+    pub render: Option<SavedRender>,
+    pub name: Option<Name>,
+    pub description: Option<Description>,
+    pub player: Option<Player>,
+    pub enemy: Option<Enemy>,
+    pub moving_randomly: Option<MovingRandomly>,
+    pub chasing_player: Option<ChasingPlayer>,
+    pub flees_when_hurt: Option<FleesWhenHurt>,
+    pub keeps_distance: Option<KeepsDistance>,
+    pub patrolling: Option<(Vec<(i32, i32)>, usize)>,
+    pub guarding: Option<((i32, i32), i32)>,
+    pub sleeping: Option<Sleeping>,
+    pub opens_doors: Option<OpensDoors>,
+    pub swims: Option<Swims>,
+    pub hidden: Option<Hidden>,
+    pub trap: Option<Trap>,
+    pub secret_door: Option<SecretDoor>,
+    pub boss: Option<Boss>,
+    pub special_attacks: Option<SpecialAttacks>,
+    pub last_seen_player: Option<((i32, i32), bool, i32)>,
+    pub health: Option<Health>,
+    pub experience: Option<Experience>,
+    pub experience_value: Option<ExperienceValue>,
+    pub status_effects: Option<StatusEffects>,
+    pub speed: Option<Speed>,
+    pub energy: Option<Energy>,
+    pub inflicts_status: Option<InflictsStatus>,
+    pub fov_radius: Option<i32>,
+    pub item: Option<Item>,
+    pub amulet: Option<AmuletOfYala>,
+    pub provides: Option<Provides>,
+    pub damage: Option<Damage>,
+    pub weapon: Option<Weapon>,
+    pub ranged: Option<Ranged>,
+    pub throwable: Option<Throwable>,
+    pub defense: Option<Defense>,
+    pub armor: Option<Armor>,
+    pub equippable: Option<Equippable>,
+    pub carried_by: Option<usize>,
+    pub equipped: Option<(usize, EquipmentSlot)>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SaveGame {
+    pub map: Map,
+    pub theme: ThemeKind,
+    pub turn_state: TurnState,
+    pub seed: Seed,
+    pub rng_seed: u64,
+    pub log: GameLog,
+    pub entities: Vec<SavedEntity>,
+    // the levels the player has left behind
+    #[serde(default)]
+    pub dungeon: Dungeon,
+    #[serde(default)]
+    pub summary: RunSummary,
+}
+
+fn rgba_to_tuple(color: RGBA) -> (f32, f32, f32, f32) {
+    (color.r, color.g, color.b, color.a)
+}
+
+fn tuple_to_rgba(color: (f32, f32, f32, f32)) -> RGBA {
+    RGBA::from_f32(color.0, color.1, color.2, color.3)
+}
+
+impl SaveGame {
+    pub fn exists() -> bool {
+        Path::new(SAVE_FILE).exists()
+    }
+
+    pub fn capture(ecs: &World, resources: &Resources) -> Self {
+        // message entities (WantsToMove etc.) have no name and are not persisted
+        let entities: Vec<Entity> = <(Entity, &Name)>::query()
+            .iter(ecs)
+            .map(|(entity, _)| *entity)
+            .collect();
+        let saved = capture_entities(ecs, &entities);
+
+        let map = resources.get::<Map>().unwrap();
+        Self {
+            map: Map {
+                width: map.width,
+                height: map.height,
+                tiles: map.tiles.clone(),
+                revealed_tiles: map.revealed_tiles.clone(),
+                movement: map.movement,
+            },
+            theme: resources.get::<Box<dyn MapTheme>>().unwrap().kind(),
+            // aiming refers to live entities, so a restored game starts back at the prompt
+            turn_state: match *resources.get::<TurnState>().unwrap() {
+                TurnState::Targeting => TurnState::AwaitingInput,
+                state => state,
+            },
+            seed: *resources.get::<Seed>().unwrap(),
+            // the generator's state can't be serialized, so reseed it from its next value
+            rng_seed: resources
+                .get_mut::<RandomNumberGenerator>()
+                .unwrap()
+                .next_u64(),
+            log: resources.get::<GameLog>().unwrap().clone(),
+            entities: saved,
+            dungeon: resources.get::<Dungeon>().unwrap().clone(),
+            summary: resources.get::<RunSummary>().unwrap().clone(),
+        }
+    }
+
+    pub fn restore(self, ecs: &mut World, resources: &mut Resources) {
+        restore_entities(ecs, &self.entities);
+
+        let player_start = <&Point>::query()
+            .filter(component::<Player>())
+            .iter(ecs)
+            .next()
+            .cloned()
+            .unwrap_or_else(Point::zero);
+
+        resources.insert(self.map);
+        resources.insert(Camera::new(player_start));
+        resources.insert(self.turn_state);
+        resources.insert(self.theme.build());
+        resources.insert(self.seed);
+        resources.insert(self.log);
+        resources.insert(self.dungeon);
+        resources.insert(self.summary);
+        let templates = Templates::load();
+        let vaults = Vaults::load();
+        resources.insert(Progression::load(&templates, &vaults));
+        resources.insert(templates);
+        resources.insert(vaults);
+        resources.insert(InventoryCursor::default());
+        resources.insert(Targeting::default());
+        resources.insert(RandomNumberGenerator::seeded(self.rng_seed));
+    }
+
+    pub fn write(&self, path: &str) {
+        let contents =
+            to_string_pretty(self, PrettyConfig::default()).expect("Unable to serialize save game");
+        let mut file = File::create(path).expect("Failed creating save file");
+        file.write_all(contents.as_bytes())
+            .expect("Failed writing save file");
+    }
+
+    // a save from an older version or a damaged file is an error, not a crash
+    pub fn load(path: &str) -> Result<Self, String> {
+        let file = File::open(path).map_err(|err| format!("Failed opening {}: {}", path, err))?;
+        from_reader(file).map_err(|err| format!("Unable to load {}: {}", path, err))
+    }
+
+    pub fn delete() {
+        if Self::exists() {
+            remove_file(SAVE_FILE).expect("Failed removing save file");
+        }
+    }
+}
+
+// Carried and Equipped only survive when the owner is among `entities` too
+pub fn capture_entities(ecs: &World, entities: &[Entity]) -> Vec<SavedEntity> {
+    let indices: HashMap<Entity, usize> = entities
+        .iter()
+        .enumerate()
+        .map(|(idx, entity)| (*entity, idx))
+        .collect();
+
+    entities
+        .iter()
+        .map(|entity| {
+            let entry = ecs.entry_ref(*entity).unwrap();
+            SavedEntity {
+                position: entry.get_component::<Point>().ok().map(|pt| (pt.x, pt.y)),
+                render: entry
+                    .get_component::<Render>()
+                    .ok()
+                    .map(|render| SavedRender {
+                        fg: rgba_to_tuple(render.color.fg),
+                        bg: rgba_to_tuple(render.color.bg),
+                        glyph: render.glyph,
+                    }),
+                name: entry.get_component::<Name>().ok().cloned(),
+                description: entry.get_component::<Description>().ok().cloned(),
+                player: entry.get_component::<Player>().ok().cloned(),
+                enemy: entry.get_component::<Enemy>().ok().cloned(),
+                moving_randomly: entry.get_component::<MovingRandomly>().ok().cloned(),
+                chasing_player: entry.get_component::<ChasingPlayer>().ok().cloned(),
+                flees_when_hurt: entry.get_component::<FleesWhenHurt>().ok().cloned(),
+                keeps_distance: entry.get_component::<KeepsDistance>().ok().cloned(),
+                patrolling: entry.get_component::<Patrolling>().ok().map(|patrol| {
+                    (
+                        patrol.waypoints.iter().map(|pt| (pt.x, pt.y)).collect(),
+                        patrol.next,
+                    )
+                }),
+                guarding: entry
+                    .get_component::<Guarding>()
+                    .ok()
+                    .map(|guard| ((guard.post.x, guard.post.y), guard.radius)),
+                sleeping: entry.get_component::<Sleeping>().ok().cloned(),
+                opens_doors: entry.get_component::<OpensDoors>().ok().cloned(),
+                swims: entry.get_component::<Swims>().ok().cloned(),
+                hidden: entry.get_component::<Hidden>().ok().cloned(),
+                trap: entry.get_component::<Trap>().ok().cloned(),
+                secret_door: entry.get_component::<SecretDoor>().ok().cloned(),
+                boss: entry.get_component::<Boss>().ok().cloned(),
+                special_attacks: entry.get_component::<SpecialAttacks>().ok().cloned(),
+                last_seen_player: entry
+                    .get_component::<LastSeenPlayer>()
+                    .ok()
+                    .map(|last_seen| {
+                        (
+                            (last_seen.pos.x, last_seen.pos.y),
+                            last_seen.arrived,
+                            last_seen.search_turns,
+                        )
+                    }),
+                health: entry.get_component::<Health>().ok().cloned(),
+                experience: entry.get_component::<Experience>().ok().cloned(),
+                experience_value: entry.get_component::<ExperienceValue>().ok().cloned(),
+                status_effects: entry.get_component::<StatusEffects>().ok().cloned(),
+                speed: entry.get_component::<Speed>().ok().cloned(),
+                energy: entry.get_component::<Energy>().ok().cloned(),
+                inflicts_status: entry.get_component::<InflictsStatus>().ok().cloned(),
+                fov_radius: entry
+                    .get_component::<FieldOfView>()
+                    .ok()
+                    .map(|fov| fov.radius),
+                item: entry.get_component::<Item>().ok().cloned(),
+                amulet: entry.get_component::<AmuletOfYala>().ok().cloned(),
+                provides: entry.get_component::<Provides>().ok().cloned(),
+                damage: entry.get_component::<Damage>().ok().cloned(),
+                weapon: entry.get_component::<Weapon>().ok().cloned(),
+                ranged: entry.get_component::<Ranged>().ok().cloned(),
+                throwable: entry.get_component::<Throwable>().ok().cloned(),
+                defense: entry.get_component::<Defense>().ok().cloned(),
+                armor: entry.get_component::<Armor>().ok().cloned(),
+                equippable: entry.get_component::<Equippable>().ok().cloned(),
+                equipped: entry.get_component::<Equipped>().ok().and_then(|equipped| {
+                    indices
+                        .get(&equipped.owner)
+                        .map(|owner| (*owner, equipped.slot))
+                }),
+                carried_by: entry
+                    .get_component::<Carried>()
+                    .ok()
+                    .and_then(|carried| indices.get(&carried.0).cloned()),
+            }
+        })
+        .collect()
+}
+
+pub fn restore_entities(ecs: &mut World, entities: &[SavedEntity]) -> Vec<Entity> {
+    let spawned: Vec<Entity> = entities
+        .iter()
+        .map(|saved| {
+            let entity = ecs.push(());
+            let mut entry = ecs.entry(entity).unwrap();
+            if let Some((x, y)) = saved.position {
+                entry.add_component(Point::new(x, y));
+            }
+            if let Some(render) = &saved.render {
+                entry.add_component(Render {
+                    color: ColorPair::new(tuple_to_rgba(render.fg), tuple_to_rgba(render.bg)),
+                    glyph: render.glyph,
+                });
+            }
+            if let Some(name) = &saved.name {
+                entry.add_component(name.clone());
+            }
+            if let Some(description) = &saved.description {
+                entry.add_component(description.clone());
+            }
+            if let Some(player) = saved.player {
+                entry.add_component(player);
+            }
+            if let Some(enemy) = saved.enemy {
+                entry.add_component(enemy);
+            }
+            if let Some(moving_randomly) = saved.moving_randomly {
+                entry.add_component(moving_randomly);
+            }
+            if let Some(chasing_player) = saved.chasing_player {
+                entry.add_component(chasing_player);
+            }
+            if let Some(flees_when_hurt) = saved.flees_when_hurt {
+                entry.add_component(flees_when_hurt);
+            }
+            if let Some(keeps_distance) = saved.keeps_distance {
+                entry.add_component(keeps_distance);
+            }
+            if let Some((waypoints, next)) = &saved.patrolling {
+                entry.add_component(Patrolling {
+                    waypoints: waypoints.iter().map(|(x, y)| Point::new(*x, *y)).collect(),
+                    next: *next,
+                });
+            }
+            if let Some(((x, y), radius)) = saved.guarding {
+                entry.add_component(Guarding {
+                    post: Point::new(x, y),
+                    radius,
+                });
+            }
+            if let Some(sleeping) = saved.sleeping {
+                entry.add_component(sleeping);
+            }
+            if let Some(opens_doors) = saved.opens_doors {
+                entry.add_component(opens_doors);
+            }
+            if let Some(swims) = saved.swims {
+                entry.add_component(swims);
+            }
+            if let Some(hidden) = saved.hidden {
+                entry.add_component(hidden);
+            }
+            if let Some(trap) = saved.trap {
+                entry.add_component(trap);
+            }
+            if let Some(secret_door) = saved.secret_door {
+                entry.add_component(secret_door);
+            }
+            if let Some(boss) = saved.boss {
+                entry.add_component(boss);
+            }
+            if let Some(special_attacks) = &saved.special_attacks {
+                entry.add_component(special_attacks.clone());
+            }
+            if let Some(((x, y), arrived, search_turns)) = saved.last_seen_player {
+                entry.add_component(LastSeenPlayer {
+                    pos: Point::new(x, y),
+                    arrived,
+                    search_turns,
+                });
+            }
+            if let Some(health) = saved.health {
+                entry.add_component(health);
+            }
+            if let Some(experience) = saved.experience {
+                entry.add_component(experience);
+            }
+            if let Some(experience_value) = saved.experience_value {
+                entry.add_component(experience_value);
+            }
+            if let Some(status_effects) = &saved.status_effects {
+                entry.add_component(status_effects.clone());
+            }
+            if let Some(speed) = saved.speed {
+                entry.add_component(speed);
+            }
+            if let Some(energy) = saved.energy {
+                entry.add_component(energy);
+            }
+            if let Some(inflicts_status) = &saved.inflicts_status {
+                entry.add_component(inflicts_status.clone());
+            }
+            if let Some(radius) = saved.fov_radius {
+                entry.add_component(FieldOfView::new(radius));
+            }
+            if let Some(item) = saved.item {
+                entry.add_component(item);
+            }
+            if let Some(amulet) = saved.amulet {
+                entry.add_component(amulet);
+            }
+            if let Some(provides) = &saved.provides {
+                entry.add_component(provides.clone());
+            }
+            if let Some(damage) = saved.damage {
+                entry.add_component(damage);
+            }
+            if let Some(weapon) = saved.weapon {
+                entry.add_component(weapon);
+            }
+            if let Some(ranged) = saved.ranged {
+                entry.add_component(ranged);
+            }
+            if let Some(throwable) = saved.throwable {
+                entry.add_component(throwable);
+            }
+            if let Some(defense) = saved.defense {
+                entry.add_component(defense);
+            }
+            if let Some(armor) = saved.armor {
+                entry.add_component(armor);
+            }
+            if let Some(equippable) = saved.equippable {
+                entry.add_component(equippable);
+            }
+            entity
+        })
+        .collect();
+
+    // owners may appear after the items they carry, so link them once everything exists
+    entities
+        .iter()
+        .zip(spawned.iter())
+        .for_each(|(saved, entity)| {
+            let mut entry = ecs.entry(*entity).unwrap();
+            if let Some(carrier) = saved.carried_by {
+                entry.add_component(Carried(spawned[carrier]));
+            }
+            if let Some((owner, slot)) = saved.equipped {
+                entry.add_component(Equipped {
+                    owner: spawned[owner],
+                    slot,
+                });
+            }
+        });
+    spawned
+}
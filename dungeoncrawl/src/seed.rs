@@ -0,0 +1,20 @@
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Seed(pub u64);
+
+impl Seed {
+    pub fn random() -> Self {
+        Self(RandomNumberGenerator::new().next_u64())
+    }
+
+    // accepts `--seed <number>` on the command line, otherwise rolls a fresh one
+    pub fn from_args() -> Self {
+        number_arg("--seed").map(Self).unwrap_or_else(Self::random)
+    }
+
+    pub fn rng(&self) -> RandomNumberGenerator {
+        RandomNumberGenerator::seeded(self.0)
+    }
+}
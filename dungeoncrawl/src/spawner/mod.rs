@@ -1,10 +1,11 @@
 use crate::prelude::*;
-use template::Templates;
+use std::collections::HashSet;
 
 mod template;
+pub use template::Templates;
 
 pub fn spawn_player(ecs: &mut World, pos: Point) {
-    ecs.push((
+    let player = ecs.push((
         Player { map_level: 0 },
         pos,
         Render {
@@ -19,6 +20,12 @@ pub fn spawn_player(ecs: &mut World, pos: Point) {
         FieldOfView::new(8),
         Damage(1),
     ));
+    // legion only builds entities from tuples of up to eight components
+    let mut entry = ecs.entry(player).unwrap();
+    entry.add_component(Experience::default());
+    entry.add_component(StatusEffects::default());
+    entry.add_component(Speed(NORMAL_SPEED));
+    entry.add_component(Energy(ACTION_COST));
 }
 
 pub fn spawn_amulet_of_yala(ecs: &mut World, pos: Point) {
@@ -37,9 +44,71 @@ pub fn spawn_amulet_of_yala(ecs: &mut World, pos: Point) {
 pub fn spawn_level(
     ecs: &mut World,
     rng: &mut RandomNumberGenerator,
-    level: usize,
-    spawn_points: &[Point],
+    templates: &Templates,
+    plan: &LevelPlan,
+    map_builder: &MapBuilder,
+    uniques: &mut HashSet<String>,
 ) {
-    let template = Templates::load();
-    template.spawn_entities(ecs, rng, level, spawn_points);
+    // bosses wait where a vault sets a place aside for them, or else as far from the way in as possible
+    let mut spawn_points = map_builder.monster_spawns.clone();
+    spawn_points.sort_by_key(|pt| {
+        -(DistanceAlg::Pythagoras.distance2d(*pt, map_builder.player_start) as i32)
+    });
+    let mut boss_spawns = map_builder.boss_spawns.clone();
+    for name in plan.bosses.iter() {
+        if uniques.contains(name) {
+            continue;
+        }
+        let pt = if boss_spawns.is_empty() {
+            spawn_points.first().cloned()
+        } else {
+            Some(boss_spawns.remove(0))
+        };
+        if let Some(pt) = pt {
+            spawn_points.retain(|spawn| *spawn != pt);
+            templates.spawn_named(ecs, rng, name, &pt, &map_builder.monster_spawns, uniques);
+        }
+    }
+
+    templates.spawn_entities(
+        ecs,
+        rng,
+        &plan.templates,
+        &spawn_points,
+        &map_builder.guard_posts,
+        &map_builder.item_spawns,
+        uniques,
+    );
+    map_builder
+        .trap_spawns
+        .iter()
+        .for_each(|pos| spawn_trap(ecs, rng, *pos));
+    map_builder
+        .secret_doors
+        .iter()
+        .for_each(|pos| spawn_secret_door(ecs, *pos));
+}
+
+// the font has no trap tile, so a found trap shows as a stain on the floor
+pub fn spawn_trap(ecs: &mut World, rng: &mut RandomNumberGenerator, pos: Point) {
+    let (trap, name, color) = match rng.range(0, 3) {
+        0 => (Trap::Dart(2), "Dart Trap", RED),
+        1 => (Trap::Teleport, "Teleport Trap", MAGENTA),
+        _ => (Trap::Alarm, "Alarm Trap", YELLOW),
+    };
+    ecs.push((
+        trap,
+        Hidden,
+        pos,
+        Render {
+            color: ColorPair::new(color, BLACK),
+            glyph: to_cp437('.'),
+        },
+        Name(name.to_string()),
+    ));
+}
+
+// named like everything else that gets saved
+pub fn spawn_secret_door(ecs: &mut World, pos: Point) {
+    ecs.push((SecretDoor, Hidden, pos, Name("Secret Door".to_string())));
 }
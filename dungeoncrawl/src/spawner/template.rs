@@ -8,15 +8,61 @@ use std::fs::File;
 #[derive(Clone, Deserialize, Debug)]
 pub struct Template {
     pub entity_type: EntityType,
-    pub levels: HashSet<usize>,
     pub frequency: i32,
     pub name: String,
+    pub description: Option<String>,
     pub glyph: char,
-    pub provides: Option<Vec<(String, i32)>>,
+    pub provides: Option<Vec<Effect>>,
     pub hp: Option<i32>,
     pub base_damage: Option<i32>,
+    pub defense: Option<i32>,
+    pub xp: Option<i32>,
+    pub on_hit: Option<Vec<(StatusKind, i32)>>,
+    pub slot: Option<EquipKind>,
+    pub range: Option<i32>,
+    pub throwable: Option<bool>,
+    pub behaviour: Option<Vec<Behaviour>>,
+    pub speed: Option<i32>,
+    pub opens_doors: Option<bool>,
+    pub swims: Option<bool>,
+    // spawns at most once a run
+    pub unique: Option<bool>,
+    // a unique with a health bar of its own, whose death makes the run summary
+    pub boss: Option<bool>,
+    // tints the glyph, so a unique stands out from the monsters that share it
+    pub color: Option<(u8, u8, u8)>,
+    pub specials: Option<Vec<SpecialAttack>>,
+    // turns between special attacks
+    pub special_cooldown: Option<i32>,
 }
 
+impl Template {
+    fn is_unique(&self) -> bool {
+        self.unique.unwrap_or(false) || self.boss.unwrap_or(false)
+    }
+}
+
+// how an enemy acts; they combine, so [Sleep, Chase] dozes until it notices you and then hunts you down
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+pub enum Behaviour {
+    Chase,
+    Wander,
+    // run away once health drops to this
+    Flee(i32),
+    // shoot from range, backing off when the player is closer than this
+    KeepDistance(i32),
+    // walk between this many spawn points
+    Patrol(usize),
+    // defend the spawn point against anyone within this many tiles
+    Guard(i32),
+    Sleep,
+}
+
+// vault defenders hold their ground whatever their template says
+const VAULT_GUARD_RADIUS: i32 = 5;
+
+const DEFAULT_SPECIAL_COOLDOWN: i32 = 4;
+
 #[derive(Clone, Deserialize, Debug, PartialEq)]
 pub enum EntityType {
     Enemy,
@@ -34,52 +80,184 @@ impl Templates {
         from_reader(file).expect("Unable to load templates")
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn spawn_entities(
         &self,
         ecs: &mut World,
         rng: &mut RandomNumberGenerator,
-        level: usize,
+        pool: &[String],
         spawn_points: &[Point],
+        guard_posts: &[Point],
+        item_spawns: &[Point],
+        uniques: &mut HashSet<String>,
     ) {
         let mut available_entities = Vec::new();
-        self.entities
-            .iter()
-            .filter(|e| e.levels.contains(&level))
+        pool.iter()
+            .filter(|name| !uniques.contains(*name))
+            .map(|name| self.named(name))
             .for_each(|t| {
                 for _ in 0..t.frequency {
                     available_entities.push(t);
                 }
             });
 
+        // item spots only ever get items
+        let mut available_items: Vec<&Template> = available_entities
+            .iter()
+            .filter(|t| t.entity_type == EntityType::Item)
+            .cloned()
+            .collect();
+
         let mut commands = CommandBuffer::new(ecs);
         spawn_points.iter().for_each(|pt| {
-            if let Some(entity) = rng.random_slice_entry(&available_entities) {
-                self.spawn_entity(pt, entity, &mut commands);
+            if let Some(entity) = rng.random_slice_entry(&available_entities).cloned() {
+                if entity.is_unique() {
+                    uniques.insert(entity.name.clone());
+                    available_entities.retain(|t| t.name != entity.name);
+                    available_items.retain(|t| t.name != entity.name);
+                }
+                let behaviours = if guard_posts.contains(pt) {
+                    vec![Behaviour::Guard(VAULT_GUARD_RADIUS)]
+                } else {
+                    entity
+                        .behaviour
+                        .clone()
+                        .unwrap_or_else(|| vec![Behaviour::Chase])
+                };
+                self.spawn_entity(pt, entity, &behaviours, rng, spawn_points, &mut commands);
+            }
+        });
+        item_spawns.iter().for_each(|pt| {
+            if let Some(item) = rng.random_slice_entry(&available_items).cloned() {
+                if item.is_unique() {
+                    uniques.insert(item.name.clone());
+                    available_items.retain(|t| t.name != item.name);
+                }
+                self.spawn_entity(pt, item, &[], rng, spawn_points, &mut commands);
             }
         });
         commands.flush(ecs);
     }
 
+    // spawns one particular template, behaving as its template says, unless it is a unique already seen
+    pub fn spawn_named(
+        &self,
+        ecs: &mut World,
+        rng: &mut RandomNumberGenerator,
+        name: &str,
+        pt: &Point,
+        spawn_points: &[Point],
+        uniques: &mut HashSet<String>,
+    ) {
+        let template = self.named(name);
+        if template.is_unique() && !uniques.insert(template.name.clone()) {
+            return;
+        }
+        let behaviours = template
+            .behaviour
+            .clone()
+            .unwrap_or_else(|| vec![Behaviour::Chase]);
+        let mut commands = CommandBuffer::new(ecs);
+        self.spawn_entity(pt, template, &behaviours, rng, spawn_points, &mut commands);
+        commands.flush(ecs);
+    }
+
+    // brings a monster into a fight already under way, so it never starts asleep
+    pub fn summon(
+        &self,
+        commands: &mut CommandBuffer,
+        rng: &mut RandomNumberGenerator,
+        name: &str,
+        pt: &Point,
+    ) {
+        let template = self.named(name);
+        let mut behaviours: Vec<Behaviour> = template
+            .behaviour
+            .clone()
+            .unwrap_or_else(|| vec![Behaviour::Chase])
+            .into_iter()
+            .filter(|behaviour| *behaviour != Behaviour::Sleep)
+            .collect();
+        if behaviours.is_empty() {
+            behaviours.push(Behaviour::Chase);
+        }
+        self.spawn_entity(pt, template, &behaviours, rng, &[*pt], commands);
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.entities.iter().any(|t| t.name == name)
+    }
+
+    fn named(&self, name: &str) -> &Template {
+        self.entities
+            .iter()
+            .find(|t| t.name == name)
+            .unwrap_or_else(|| panic!("No template named {}", name))
+    }
+
     fn spawn_entity(
         &self,
         pt: &Point,
         template: &Template,
+        behaviours: &[Behaviour],
+        rng: &mut RandomNumberGenerator,
+        spawn_points: &[Point],
         commands: &mut legion::systems::CommandBuffer,
     ) {
         let entity = commands.push((
             pt.clone(),
             Render {
-                color: ColorPair::new(WHITE, BLACK),
+                color: ColorPair::new(template.color.unwrap_or(WHITE), BLACK),
                 glyph: to_cp437(template.glyph),
             },
             Name(template.name.clone()),
         ));
+        if let Some(description) = &template.description {
+            commands.add_component(entity, Description(description.clone()));
+        }
         match template.entity_type {
             EntityType::Item => commands.add_component(entity, Item {}),
             EntityType::Enemy => {
                 commands.add_component(entity, Enemy {});
                 commands.add_component(entity, FieldOfView::new(6));
-                commands.add_component(entity, ChasingPlayer {});
+                commands.add_component(entity, StatusEffects::default());
+                commands.add_component(entity, Speed(template.speed.unwrap_or(NORMAL_SPEED)));
+                commands.add_component(entity, Energy::default());
+                if template.opens_doors.unwrap_or(true) {
+                    commands.add_component(entity, OpensDoors);
+                }
+                if template.swims.unwrap_or(true) {
+                    commands.add_component(entity, Swims);
+                }
+                behaviours.iter().for_each(|behaviour| match behaviour {
+                    Behaviour::Chase => commands.add_component(entity, ChasingPlayer {}),
+                    Behaviour::Wander => commands.add_component(entity, MovingRandomly {}),
+                    Behaviour::Flee(below) => {
+                        commands.add_component(entity, FleesWhenHurt { below: *below })
+                    }
+                    Behaviour::KeepDistance(min) => {
+                        commands.add_component(entity, KeepsDistance { min: *min })
+                    }
+                    Behaviour::Patrol(stops) => {
+                        // patrollers give chase when they spot the player
+                        let mut waypoints = vec![*pt];
+                        for _ in 1..*stops {
+                            if let Some(stop) = rng.random_slice_entry(spawn_points) {
+                                waypoints.push(*stop);
+                            }
+                        }
+                        commands.add_component(entity, Patrolling { waypoints, next: 0 });
+                        commands.add_component(entity, ChasingPlayer {});
+                    }
+                    Behaviour::Guard(radius) => commands.add_component(
+                        entity,
+                        Guarding {
+                            post: *pt,
+                            radius: *radius,
+                        },
+                    ),
+                    Behaviour::Sleep => commands.add_component(entity, Sleeping {}),
+                });
                 commands.add_component(
                     entity,
                     Health {
@@ -90,13 +268,17 @@ impl Templates {
             }
         }
         if let Some(effects) = &template.provides {
-            effects
+            commands.add_component(entity, Provides(effects.clone()));
+        }
+        if let Some(on_hit) = &template.on_hit {
+            let effects = on_hit
                 .iter()
-                .for_each(|(provides, n)| match provides.as_str() {
-                    "Healing" => commands.add_component(entity, ProvidesHealing { amount: *n }),
-                    "MagicMap" => commands.add_component(entity, ProvidesDungeonMap {}),
-                    _ => println!("Warning: we don't know how to provide {}", provides),
-                });
+                .map(|(kind, turns)| StatusEffect {
+                    kind: *kind,
+                    turns: *turns,
+                })
+                .collect();
+            commands.add_component(entity, InflictsStatus(effects));
         }
         if let Some(damage) = &template.base_damage {
             commands.add_component(entity, Damage(*damage));
@@ -104,5 +286,51 @@ impl Templates {
                 commands.add_component(entity, Weapon {});
             }
         }
+        if let Some(xp) = &template.xp {
+            commands.add_component(entity, ExperienceValue(*xp));
+        }
+        if let Some(defense) = &template.defense {
+            commands.add_component(entity, Defense(*defense));
+            if template.entity_type == EntityType::Item {
+                commands.add_component(entity, Armor {});
+            }
+        }
+        if template.boss.unwrap_or(false) {
+            commands.add_component(entity, Boss);
+        }
+        if let Some(attacks) = &template.specials {
+            commands.add_component(
+                entity,
+                SpecialAttacks {
+                    attacks: attacks.clone(),
+                    cooldown: template
+                        .special_cooldown
+                        .unwrap_or(DEFAULT_SPECIAL_COOLDOWN),
+                    recharge: 0,
+                },
+            );
+        }
+        if let Some(range) = &template.range {
+            commands.add_component(entity, Ranged { range: *range });
+        }
+        let throwable = template.throwable.unwrap_or(false);
+        if throwable {
+            commands.add_component(entity, Throwable {});
+        }
+        if template.entity_type == EntityType::Item && !throwable {
+            // weapons go in the main hand and armor on the body unless the template says otherwise
+            let kind = template.slot.or_else(|| {
+                if template.base_damage.is_some() {
+                    Some(EquipKind::MainHand)
+                } else if template.defense.is_some() {
+                    Some(EquipKind::Body)
+                } else {
+                    None
+                }
+            });
+            if let Some(kind) = kind {
+                commands.add_component(entity, Equippable(kind));
+            }
+        }
     }
 }
@@ -0,0 +1,135 @@
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+
+// hp lost to poison and regained by regeneration each round
+pub const POISON_DAMAGE: i32 = 1;
+pub const REGENERATION_HEAL: i32 = 1;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum StatusKind {
+    Poison,
+    Regeneration,
+    Confusion,
+    Slow,
+    Haste,
+    Fear,
+    Telepathy,
+}
+
+impl StatusKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            StatusKind::Poison => "Poison",
+            StatusKind::Regeneration => "Regeneration",
+            StatusKind::Confusion => "Confusion",
+            StatusKind::Slow => "Slow",
+            StatusKind::Haste => "Haste",
+            StatusKind::Fear => "Fear",
+            StatusKind::Telepathy => "Telepathy",
+        }
+    }
+
+    pub fn adjective(&self) -> &'static str {
+        match self {
+            StatusKind::Poison => "poisoned",
+            StatusKind::Regeneration => "regenerating",
+            StatusKind::Confusion => "confused",
+            StatusKind::Slow => "slowed",
+            StatusKind::Haste => "hasted",
+            StatusKind::Fear => "afraid",
+            StatusKind::Telepathy => "telepathic",
+        }
+    }
+
+    pub fn color(&self) -> (u8, u8, u8) {
+        match self {
+            StatusKind::Poison => GREEN,
+            StatusKind::Regeneration => PINK,
+            StatusKind::Confusion => MAGENTA,
+            StatusKind::Slow => CYAN,
+            StatusKind::Haste => ORANGE,
+            StatusKind::Fear => YELLOW,
+            StatusKind::Telepathy => LIGHT_BLUE,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    pub turns: i32,
+}
+
+// every timed effect on an entity; end_turn ticks them down once per round
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct StatusEffects(pub Vec<StatusEffect>);
+
+impl StatusEffects {
+    pub fn has(&self, kind: StatusKind) -> bool {
+        self.0.iter().any(|effect| effect.kind == kind)
+    }
+
+    // an effect that is already running isn't restarted, so repeated hits can't stack it forever
+    pub fn apply(&mut self, effect: StatusEffect) -> bool {
+        if self.has(effect.kind) {
+            return false;
+        }
+        self.0.push(effect);
+        true
+    }
+
+    // confused monsters can't follow their own plans this round
+    pub fn overrides_ai(&self) -> bool {
+        self.has(StatusKind::Confusion)
+    }
+
+    // haste doubles the energy earned each round and slow halves it
+    pub fn adjust_speed(&self, speed: i32) -> i32 {
+        let mut speed = speed;
+        if self.has(StatusKind::Haste) {
+            speed *= 2;
+        }
+        if self.has(StatusKind::Slow) {
+            speed /= 2;
+        }
+        i32::max(1, speed)
+    }
+}
+
+// effects a monster gives whoever it hits
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct InflictsStatus(pub Vec<StatusEffect>);
+
+// callers need read access to Name and write access to StatusEffects
+pub fn apply_status(
+    ecs: &mut SubWorld,
+    commands: &mut CommandBuffer,
+    target: Entity,
+    effects: &[StatusEffect],
+    log: &mut GameLog,
+) {
+    let mut entry = match ecs.entry_mut(target) {
+        Ok(entry) => entry,
+        Err(_) => return,
+    };
+    let name = entry
+        .get_component::<Name>()
+        .map(|name| name.0.clone())
+        .unwrap_or_else(|_| "something".to_string());
+    let applied: Vec<StatusKind> = if let Ok(active) = entry.get_component_mut::<StatusEffects>() {
+        effects
+            .iter()
+            .filter(|effect| active.apply(**effect))
+            .map(|effect| effect.kind)
+            .collect()
+    } else {
+        commands.add_component(target, StatusEffects(effects.to_vec()));
+        effects.iter().map(|effect| effect.kind).collect()
+    };
+    applied.into_iter().for_each(|status| {
+        log.push(GameEvent::StatusApplied {
+            target: name.clone(),
+            status,
+        })
+    });
+}
@@ -1,65 +1,283 @@
+use super::random_move::random_direction;
 use crate::prelude::*;
 
+// how long a monster hunts around the spot it lost the player
+pub const SEARCH_TURNS: i32 = 5;
+// and how far from that spot it strays while doing so
+const SEARCH_RADIUS: f32 = 3.0;
+
 #[system]
 #[read_component(Point)]
 #[read_component(ChasingPlayer)]
 #[read_component(FieldOfView)]
 #[read_component(Health)]
 #[read_component(Player)]
-pub fn chasing(#[resource] map: &Map, ecs: &SubWorld, commands: &mut CommandBuffer) {
-    let mut movers = <(Entity, &Point, &ChasingPlayer, &FieldOfView)>::query();
-    let mut positions = <(Entity, &Point, &Health)>::query();
+#[read_component(StatusEffects)]
+#[read_component(Energy)]
+#[read_component(Acted)]
+#[read_component(FleesWhenHurt)]
+#[read_component(Sleeping)]
+#[read_component(LastSeenPlayer)]
+#[read_component(OpensDoors)]
+#[read_component(Swims)]
+pub fn chasing(
+    #[resource] map: &Map,
+    #[resource] rng: &mut RandomNumberGenerator,
+    ecs: &SubWorld,
+    commands: &mut CommandBuffer,
+) {
+    let mut movers =
+        <(Entity, &Point, &ChasingPlayer, &FieldOfView)>::query().filter(!component::<Sleeping>());
     let mut player = <(&Point, &Player)>::query();
 
     let player_pos = player.iter(ecs).nth(0).unwrap().0;
-    let player_idx = map_idx(player_pos.x, player_pos.y);
 
-    let search_targets = vec![player_idx];
-    let dijkstra_map = DijkstraMap::new(SCREEN_WIDTH, SCREEN_HEIGHT, &search_targets, map, 1024.0);
+    let mut distances = PlayerDistances::new(*player_pos);
 
     movers.iter(ecs).for_each(|(entity, pos, _, fov)| {
+        // confused monsters are moved by random_move instead
         if !fov.visible_tiles.contains(&player_pos) {
+            if can_act(ecs, *entity) && !overridden(ecs, *entity) {
+                search(map, rng, ecs, commands, *entity, *pos);
+            }
+            return;
+        }
+        commands.add_component(
+            *entity,
+            LastSeenPlayer {
+                pos: *player_pos,
+                arrived: false,
+                search_turns: SEARCH_TURNS,
+            },
+        );
+        if !can_act(ecs, *entity) || overridden(ecs, *entity) {
             return;
         }
-        let idx = map_idx(pos.x, pos.y);
-        if let Some(destination) = DijkstraMap::find_lowest_exit(&dijkstra_map, idx, map) {
+        let idx = map.point2d_to_index(*pos);
+        let mobility = mobility_of(ecs, *entity);
+        let dijkstra_map = distances.get(map, mobility);
+        // frightened monsters head uphill, away from the player, and never attack
+        if is_afraid(ecs, *entity) {
+            flee(
+                dijkstra_map,
+                &map.pathing(mobility),
+                ecs,
+                commands,
+                *entity,
+                idx,
+            );
+            return;
+        }
+        if let Some(destination) =
+            DijkstraMap::find_lowest_exit(dijkstra_map, idx, &map.pathing(mobility))
+        {
             let distance = DistanceAlg::Pythagoras.distance2d(*pos, *player_pos);
             let destination = if distance > 1.2 {
                 map.index_to_point2d(destination)
             } else {
                 *player_pos
             };
-            let mut attacked = false;
-            positions
-                .iter(ecs)
-                .filter(|(_, target_pos, _)| **target_pos == destination)
-                .for_each(|(victim, _, _)| {
-                    if ecs
-                        .entry_ref(*victim)
-                        .unwrap()
-                        .get_component::<Player>()
-                        .is_ok()
-                    {
-                        commands.push((
-                            (),
-                            WantsToAttack {
-                                attacker: *entity,
-                                victim: *victim,
-                            },
-                        ));
-                    }
-                    attacked = true;
-                });
-
-            if !attacked {
+            move_or_attack(ecs, commands, *entity, destination);
+        }
+    });
+}
+
+// head for where the player was last seen, then poke around there until the trail goes cold
+fn search(
+    map: &Map,
+    rng: &mut RandomNumberGenerator,
+    ecs: &SubWorld,
+    commands: &mut CommandBuffer,
+    entity: Entity,
+    pos: Point,
+) {
+    let mut last_seen = match ecs
+        .entry_ref(entity)
+        .unwrap()
+        .get_component::<LastSeenPlayer>()
+    {
+        Ok(last_seen) => *last_seen,
+        Err(_) => return,
+    };
+    let pathing = map.pathing(mobility_of(ecs, entity));
+    if !last_seen.arrived {
+        match pathing.next_step(pos, last_seen.pos) {
+            Some(destination) if pos != last_seen.pos => {
+                move_or_attack(ecs, commands, entity, destination);
+                return;
+            }
+            // there, or no way through
+            _ => last_seen.arrived = true,
+        }
+    }
+    if last_seen.search_turns <= 0 {
+        commands.remove_component::<LastSeenPlayer>(entity);
+        return;
+    }
+    last_seen.search_turns -= 1;
+    commands.add_component(entity, last_seen);
+    let destination = pos + random_direction(rng, map.movement);
+    let destination =
+        if DistanceAlg::Pythagoras.distance2d(destination, last_seen.pos) > SEARCH_RADIUS {
+            match pathing.next_step(pos, last_seen.pos) {
+                Some(destination) => destination,
+                None => return,
+            }
+        } else {
+            destination
+        };
+    if pathing.can_step(pos, destination) {
+        move_or_attack(ecs, commands, entity, destination);
+    }
+}
+
+// the player gets attacked, anything else in the way just blocks the move
+pub fn move_or_attack(
+    ecs: &SubWorld,
+    commands: &mut CommandBuffer,
+    entity: Entity,
+    destination: Point,
+) {
+    let mut positions = <(Entity, &Point, &Health)>::query();
+    let mut attacked = false;
+    positions
+        .iter(ecs)
+        .filter(|(_, target_pos, _)| **target_pos == destination)
+        .for_each(|(victim, _, _)| {
+            if ecs
+                .entry_ref(*victim)
+                .unwrap()
+                .get_component::<Player>()
+                .is_ok()
+            {
                 commands.push((
                     (),
-                    WantsToMove {
-                        entity: *entity,
-                        destination,
+                    WantsToAttack {
+                        attacker: entity,
+                        victim: *victim,
+                        ranged: None,
                     },
                 ));
             }
+            attacked = true;
+        });
+
+    if !attacked {
+        commands.push((
+            (),
+            WantsToMove {
+                entity,
+                destination,
+            },
+        ));
+    }
+}
+
+// distances to the player, for closing in and for running away;
+// monsters that can't cross the same ground get their own maps
+pub struct PlayerDistances {
+    player_pos: Point,
+    maps: Vec<(Mobility, DijkstraMap)>,
+}
+
+impl PlayerDistances {
+    pub fn new(player_pos: Point) -> Self {
+        Self {
+            player_pos,
+            maps: Vec::new(),
         }
-    });
+    }
+
+    // only built once somebody actually needs it
+    pub fn get(&mut self, map: &Map, mobility: Mobility) -> &DijkstraMap {
+        let i = match self.maps.iter().position(|(m, _)| *m == mobility) {
+            Some(i) => i,
+            None => {
+                let start = map.point2d_to_index(self.player_pos);
+                self.maps
+                    .push((mobility, map.pathing(mobility).distances(&[start])));
+                self.maps.len() - 1
+            }
+        };
+        &self.maps[i].1
+    }
+
+    // the first step downhill towards the player, if they can be reached at all
+    pub fn next_step(&mut self, map: &Map, mobility: Mobility, from: Point) -> Option<Point> {
+        let distances = self.get(map, mobility);
+        let here = map.point2d_to_index(from);
+        DijkstraMap::find_lowest_exit(distances, here, &map.pathing(mobility))
+            .filter(|idx| distances.map[*idx] < distances.map[here])
+            .map(|idx| map.index_to_point2d(idx))
+    }
+}
+
+// callers need read access to OpensDoors and Swims; monsters never walk into hazards
+pub fn mobility_of(ecs: &SubWorld, entity: Entity) -> Mobility {
+    let entry = ecs.entry_ref(entity).ok();
+    Mobility {
+        opens_doors: entry
+            .as_ref()
+            .is_some_and(|entry| entry.get_component::<OpensDoors>().is_ok()),
+        swims: entry
+            .as_ref()
+            .is_some_and(|entry| entry.get_component::<Swims>().is_ok()),
+        hazards: false,
+    }
+}
+
+// step to the neighbouring tile furthest from the player;
+// false when there is nowhere to go
+pub fn flee(
+    dijkstra_map: &DijkstraMap,
+    map: &Pathing,
+    ecs: &SubWorld,
+    commands: &mut CommandBuffer,
+    entity: Entity,
+    idx: usize,
+) -> bool {
+    if let Some(destination) = DijkstraMap::find_highest_exit(dijkstra_map, idx, map) {
+        let destination = map.index_to_point2d(destination);
+        let blocked = <(&Point, &Health)>::query()
+            .iter(ecs)
+            .any(|(target_pos, _)| *target_pos == destination);
+        if !blocked {
+            commands.push((
+                (),
+                WantsToMove {
+                    entity,
+                    destination,
+                },
+            ));
+            return true;
+        }
+    }
+    false
+}
+
+// scared by a spell, or hurt badly enough to lose its nerve
+pub fn is_afraid(ecs: &SubWorld, entity: Entity) -> bool {
+    let entry = match ecs.entry_ref(entity) {
+        Ok(entry) => entry,
+        Err(_) => return false,
+    };
+    let frightened = entry
+        .get_component::<StatusEffects>()
+        .is_ok_and(|effects| effects.has(StatusKind::Fear));
+    let hurt = match (
+        entry.get_component::<FleesWhenHurt>(),
+        entry.get_component::<Health>(),
+    ) {
+        (Ok(flees), Ok(health)) => health.current <= flees.below,
+        _ => false,
+    };
+    frightened || hurt
+}
+
+// confusion takes over from whatever the monster had planned
+pub fn overridden(ecs: &SubWorld, entity: Entity) -> bool {
+    ecs.entry_ref(entity)
+        .ok()
+        .and_then(|entry| entry.get_component::<StatusEffects>().ok().cloned())
+        .is_some_and(|effects| effects.overrides_ai())
 }
@@ -1,56 +1,297 @@
 use crate::prelude::*;
 
+// a hit always does at least this much, however well armored the victim is
+const MIN_DAMAGE: i32 = 1;
+
+// what each character level adds to max health and base damage
+const HEALTH_PER_LEVEL: i32 = 3;
+const DAMAGE_PER_LEVEL: i32 = 1;
+
 #[system]
 #[read_component(WantsToAttack)]
 #[read_component(Player)]
 #[write_component(Health)]
-#[read_component(Damage)]
-#[read_component(Carried)]
-pub fn combat(ecs: &mut SubWorld, commands: &mut CommandBuffer) {
+#[write_component(Damage)]
+#[write_component(Experience)]
+#[read_component(ExperienceValue)]
+#[read_component(Equipped)]
+#[read_component(Defense)]
+#[read_component(Name)]
+#[read_component(InflictsStatus)]
+#[write_component(StatusEffects)]
+#[read_component(Point)]
+#[read_component(Ranged)]
+#[read_component(Throwable)]
+#[read_component(Boss)]
+#[read_component(WantsToShootTile)]
+pub fn combat(
+    ecs: &mut SubWorld,
+    commands: &mut CommandBuffer,
+    #[resource] log: &mut GameLog,
+    #[resource] map: &Map,
+    #[resource] summary: &mut RunSummary,
+) {
     // get all entities who want to attack
     let mut attackers = <(Entity, &WantsToAttack)>::query();
 
     // get list of victim
-    let victims: Vec<(Entity, Entity, Entity)> = attackers
+    let victims: Vec<(Entity, Entity, Entity, Option<Entity>)> = attackers
         .iter(ecs)
-        .map(|(entity, attack)| (*entity, attack.attacker, attack.victim))
+        .map(|(entity, attack)| (*entity, attack.attacker, attack.victim, attack.ranged))
         .collect();
 
-    victims.iter().for_each(|(message, attacker, victim)| {
-        let is_player = ecs
-            .entry_ref(*victim)
-            .unwrap()
-            .get_component::<Player>()
-            .is_ok();
+    victims
+        .iter()
+        .for_each(|(message, attacker, victim, ranged)| {
+            // an earlier attack this turn may already have finished off either side
+            if is_down(ecs, *attacker) || is_down(ecs, *victim) {
+                commands.remove(*message);
+                return;
+            }
+
+            let attacker_pos = entity_pos(ecs, *attacker);
+            let victim_pos = entity_pos(ecs, *victim);
+
+            // shots and throws need the victim within range and in plain sight
+            if let Some(weapon) = ranged {
+                let reachable = match (attacker_pos, victim_pos) {
+                    (Some(from), Some(to)) => in_range(map, from, to, weapon_range(ecs, *weapon)),
+                    _ => false,
+                };
+                if !reachable {
+                    log.push(GameEvent::OutOfRange {
+                        attacker: entity_name(ecs, *attacker),
+                        victim: entity_name(ecs, *victim),
+                    });
+                    commands.remove(*message);
+                    return;
+                }
+            }
+
+            let is_player = ecs
+                .entry_ref(*victim)
+                .unwrap()
+                .get_component::<Player>()
+                .is_ok();
 
-        let base_damage = if let Ok(v) = ecs.entry_ref(*attacker) {
-            if let Ok(dmg) = v.get_component::<Damage>() {
-                dmg.0
+            let base_damage = if let Ok(v) = ecs.entry_ref(*attacker) {
+                if let Ok(dmg) = v.get_component::<Damage>() {
+                    dmg.0
+                } else {
+                    0
+                }
             } else {
                 0
+            };
+
+            // a bow isn't much use as a club, so melee only counts hand weapons;
+            // monsters that shoot are their own weapon and already counted their damage
+            let weapon_damage: i32 = match ranged {
+                Some(weapon) if weapon == attacker => 0,
+                Some(weapon) => ecs
+                    .entry_ref(*weapon)
+                    .ok()
+                    .and_then(|entry| entry.get_component::<Damage>().ok().cloned())
+                    .map(|dmg| dmg.0)
+                    .unwrap_or(0),
+                None => <(Entity, &Equipped, &Damage)>::query()
+                    .iter(ecs)
+                    .filter(|(_, equipped, _)| equipped.owner == *attacker)
+                    .filter(|(entity, _, _)| {
+                        ecs.entry_ref(**entity)
+                            .map(|entry| entry.get_component::<Ranged>().is_err())
+                            .unwrap_or(true)
+                    })
+                    .map(|(_, _, dmg)| dmg.0)
+                    .sum(),
+            };
+
+            // thrown items land at the victim's feet
+            if let (Some(weapon), Some(landing)) = (ranged, victim_pos) {
+                if ecs
+                    .entry_ref(*weapon)
+                    .map(|entry| entry.get_component::<Throwable>().is_ok())
+                    .unwrap_or(false)
+                {
+                    commands.remove_component::<Carried>(*weapon);
+                    commands.add_component(*weapon, landing);
+                    log.push(GameEvent::Threw {
+                        item: entity_name(ecs, *weapon),
+                    });
+                }
             }
-        } else {
-            0
-        };
-
-        let weapon_damage: i32 = <(&Carried, &Damage)>::query()
-            .iter(ecs)
-            .filter(|(carried, _)| carried.0 == *attacker)
-            .map(|(_, dmg)| dmg.0)
-            .sum();
-
-        let final_damage = base_damage + weapon_damage;
-
-        if let Ok(mut health) = ecs
-            .entry_mut(*victim)
-            .unwrap()
-            .get_component_mut::<Health>()
-        {
-            health.current -= final_damage;
-            if health.current < 1 && !is_player {
-                commands.remove(*victim);
+
+            let natural_defense = if let Ok(v) = ecs.entry_ref(*victim) {
+                if let Ok(defense) = v.get_component::<Defense>() {
+                    defense.0
+                } else {
+                    0
+                }
+            } else {
+                0
+            };
+
+            let armor_defense: i32 = <(&Equipped, &Defense)>::query()
+                .iter(ecs)
+                .filter(|(equipped, _)| equipped.owner == *victim)
+                .map(|(_, defense)| defense.0)
+                .sum();
+
+            let final_damage = i32::max(
+                MIN_DAMAGE,
+                base_damage + weapon_damage - (natural_defense + armor_defense),
+            );
+
+            let attacker_name = entity_name(ecs, *attacker);
+            let victim_name = entity_name(ecs, *victim);
+
+            let inflicted = ecs
+                .entry_ref(*attacker)
+                .ok()
+                .and_then(|entry| entry.get_component::<InflictsStatus>().ok().cloned());
+
+            let mut killed = false;
+            if let Ok(health) = ecs
+                .entry_mut(*victim)
+                .unwrap()
+                .get_component_mut::<Health>()
+            {
+                health.current -= final_damage;
+                log.push(GameEvent::Attack {
+                    attacker: attacker_name.clone(),
+                    victim: victim_name.clone(),
+                    damage: final_damage,
+                });
+                killed = health.current < 1 && !is_player;
+            }
+            if killed {
+                kill(
+                    ecs,
+                    commands,
+                    log,
+                    summary,
+                    *victim,
+                    Some(*attacker),
+                    &attacker_name,
+                );
+            } else {
+                // nobody sleeps through being hit
+                commands.remove_component::<Sleeping>(*victim);
+                if let Some(inflicted) = inflicted {
+                    apply_status(ecs, commands, *victim, &inflicted.0, log);
+                }
             }
+            commands.remove(*message);
+        });
+
+    // a shot at an empty tile hits nothing, but a thrown item still lands there
+    let missed: Vec<(Entity, WantsToShootTile)> = <(Entity, &WantsToShootTile)>::query()
+        .iter(ecs)
+        .map(|(entity, shot)| (*entity, *shot))
+        .collect();
+    missed.iter().for_each(|(message, shot)| {
+        let item = entity_name(ecs, shot.weapon);
+        let thrown = ecs
+            .entry_ref(shot.weapon)
+            .map(|entry| entry.get_component::<Throwable>().is_ok())
+            .unwrap_or(false);
+        if thrown {
+            commands.remove_component::<Carried>(shot.weapon);
+            commands.add_component(shot.weapon, shot.tile);
+            log.push(GameEvent::Threw { item });
+        } else {
+            log.push(GameEvent::ShotMissed { weapon: item });
         }
         commands.remove(*message);
     });
 }
+
+// every death goes through here so the log, xp and run summary always agree; callers need
+// read access to Name, ExperienceValue and Boss, and whatever gain_experience writes
+pub fn kill(
+    ecs: &mut SubWorld,
+    commands: &mut CommandBuffer,
+    log: &mut GameLog,
+    summary: &mut RunSummary,
+    victim: Entity,
+    killer: Option<Entity>,
+    cause: &str,
+) {
+    let xp_value = ecs
+        .entry_ref(victim)
+        .ok()
+        .and_then(|entry| entry.get_component::<ExperienceValue>().ok().cloned())
+        .map(|value| value.0)
+        .unwrap_or(0);
+    commands.remove(victim);
+    log.push(GameEvent::Killed {
+        attacker: cause.to_string(),
+        victim: entity_name(ecs, victim),
+    });
+    summary.record_kill(ecs, victim);
+    if let Some(killer) = killer.filter(|_| xp_value > 0) {
+        gain_experience(ecs, killer, xp_value, log);
+    }
+}
+
+// only entities that track Experience (the player) can level up
+pub fn gain_experience(ecs: &mut SubWorld, entity: Entity, xp: i32, log: &mut GameLog) {
+    let mut entry = match ecs.entry_mut(entity) {
+        Ok(entry) => entry,
+        Err(_) => return,
+    };
+    let mut levels_gained = 0;
+    if let Ok(experience) = entry.get_component_mut::<Experience>() {
+        experience.current += xp;
+        while experience.current >= experience.next_level() {
+            experience.current -= experience.next_level();
+            experience.level += 1;
+            levels_gained += 1;
+            log.push(GameEvent::LevelUp {
+                level: experience.level,
+            });
+        }
+        if levels_gained > 0 {
+            experience.notice_turns = LEVEL_UP_NOTICE_TURNS;
+        }
+    }
+    if levels_gained == 0 {
+        return;
+    }
+    if let Ok(health) = entry.get_component_mut::<Health>() {
+        health.max += HEALTH_PER_LEVEL * levels_gained;
+        health.current += HEALTH_PER_LEVEL * levels_gained;
+    }
+    if let Ok(damage) = entry.get_component_mut::<Damage>() {
+        damage.0 += DAMAGE_PER_LEVEL * levels_gained;
+    }
+}
+
+pub fn player_entity(ecs: &SubWorld) -> Option<Entity> {
+    <Entity>::query()
+        .filter(component::<Player>())
+        .iter(ecs)
+        .next()
+        .cloned()
+}
+
+fn entity_pos(ecs: &SubWorld, entity: Entity) -> Option<Point> {
+    ecs.entry_ref(entity)
+        .ok()
+        .and_then(|entry| entry.get_component::<Point>().ok().cloned())
+}
+
+fn entity_name(ecs: &SubWorld, entity: Entity) -> String {
+    ecs.entry_ref(entity)
+        .ok()
+        .and_then(|entry| entry.get_component::<Name>().ok().cloned())
+        .map(|name| name.0)
+        .unwrap_or_else(|| "something".to_string())
+}
+
+fn is_down(ecs: &SubWorld, entity: Entity) -> bool {
+    ecs.entry_ref(entity)
+        .ok()
+        .and_then(|entry| entry.get_component::<Health>().ok().cloned())
+        .is_some_and(|health| health.current < 1)
+}
@@ -1,19 +1,46 @@
+use super::combat::{kill, player_entity};
 use crate::prelude::*;
 
+// the extra energy an action costs in deep water
+const WADING_COST: i32 = ACTION_COST;
+
 #[system]
-#[read_component(Health)]
+#[write_component(Health)]
 #[read_component(Player)]
+#[read_component(Name)]
+#[write_component(StatusEffects)]
 #[read_component(Point)]
 #[read_component(AmuletOfYala)]
-pub fn end_turn(ecs: &SubWorld, #[resource] turn_state: &mut TurnState, #[resource] map: &Map) {
+#[write_component(Experience)]
+#[write_component(Energy)]
+#[read_component(Acted)]
+#[read_component(Speed)]
+#[read_component(Boss)]
+#[read_component(ExperienceValue)]
+#[write_component(Damage)]
+pub fn end_turn(
+    ecs: &mut SubWorld,
+    commands: &mut CommandBuffer,
+    #[resource] turn_state: &mut TurnState,
+    #[resource] map: &Map,
+    #[resource] log: &mut GameLog,
+    #[resource] summary: &mut RunSummary,
+) {
     let mut player_hp = <(&Health, &Point)>::query().filter(component::<Player>());
     let mut amulet = <&Point>::query().filter(component::<AmuletOfYala>());
 
-    let current_state = turn_state.clone();
-    let mut new_state = match turn_state {
+    let current_state = *turn_state;
+    let mut new_state = match current_state {
         TurnState::AwaitingInput => return,
-        TurnState::PlayerTurn => TurnState::MonsterTurn,
-        TurnState::MonsterTurn => TurnState::AwaitingInput,
+        TurnState::PlayerTurn => {
+            spend_energy(ecs, commands, map, true);
+            next_turn(ecs, commands, log, summary)
+        }
+        TurnState::MonsterTurn => {
+            spend_energy(ecs, commands, map, false);
+            next_turn(ecs, commands, log, summary)
+        }
+        // such as movement having sent the player up or down the stairs
         _ => current_state,
     };
 
@@ -27,11 +54,142 @@ pub fn end_turn(ecs: &SubWorld, #[resource] turn_state: &mut TurnState, #[resour
         if pos == amulet_pos {
             new_state = TurnState::Victory;
         }
-        let idx = map.point2d_to_index(*pos);
-        if map.tiles[idx] == TileType::Exit {
-            new_state = TurnState::NextLevel;
-        }
     });
 
     *turn_state = new_state;
 }
+
+// whoever just acted pays for it; on a monster turn that is every monster that was ready
+fn spend_energy(ecs: &mut SubWorld, commands: &mut CommandBuffer, map: &Map, player_turn: bool) {
+    <(&mut Energy, Option<&Point>, Option<&Player>)>::query()
+        .iter_mut(ecs)
+        .filter(|(energy, _, player)| player.is_some() == player_turn && energy.ready())
+        .for_each(|(energy, pos, _)| {
+            energy.0 -= ACTION_COST;
+            // everything takes longer while wading through deep water
+            if pos.is_some_and(|pos| map.tiles[map.point2d_to_index(*pos)] == TileType::DeepWater) {
+                energy.0 -= WADING_COST;
+            }
+        });
+    <Entity>::query()
+        .filter(component::<Acted>())
+        .iter(ecs)
+        .for_each(|entity| commands.remove_component::<Acted>(*entity));
+}
+
+// let rounds pass until somebody has the energy to act; monsters go before the player
+fn next_turn(
+    ecs: &mut SubWorld,
+    commands: &mut CommandBuffer,
+    log: &mut GameLog,
+    summary: &mut RunSummary,
+) -> TurnState {
+    loop {
+        let mut energies = <(&Energy, Option<&Player>)>::query();
+        if energies
+            .iter(ecs)
+            .any(|(energy, player)| player.is_none() && energy.ready())
+        {
+            return TurnState::MonsterTurn;
+        }
+        let player_ready = <&Energy>::query()
+            .filter(component::<Player>())
+            .iter(ecs)
+            .next()
+            .map(|energy| energy.ready())
+            .unwrap_or(true);
+        let player_dead = <&Health>::query()
+            .filter(component::<Player>())
+            .iter(ecs)
+            .any(|health| health.current < 1);
+        if player_ready || player_dead {
+            return TurnState::AwaitingInput;
+        }
+        next_round(ecs, commands, log, summary);
+    }
+}
+
+// one round of game time: everyone earns energy and timed effects tick down
+fn next_round(
+    ecs: &mut SubWorld,
+    commands: &mut CommandBuffer,
+    log: &mut GameLog,
+    summary: &mut RunSummary,
+) {
+    <(&mut Energy, Option<&Speed>, Option<&StatusEffects>)>::query()
+        .iter_mut(ecs)
+        .for_each(|(energy, speed, effects)| {
+            let speed = speed.map(|speed| speed.0).unwrap_or(NORMAL_SPEED);
+            let speed = match effects {
+                Some(effects) => effects.adjust_speed(speed),
+                None => speed,
+            };
+            // however slow, everything gets a turn eventually
+            energy.0 += i32::max(1, speed);
+        });
+
+    // the level-up notice fades after a few rounds
+    <&mut Experience>::query()
+        .iter_mut(ecs)
+        .filter(|experience| experience.notice_turns > 0)
+        .for_each(|experience| experience.notice_turns -= 1);
+    tick_status_effects(ecs, commands, log, summary);
+}
+
+fn tick_status_effects(
+    ecs: &mut SubWorld,
+    commands: &mut CommandBuffer,
+    log: &mut GameLog,
+    summary: &mut RunSummary,
+) {
+    let mut killed = Vec::new();
+    <(
+        Entity,
+        &Name,
+        &mut StatusEffects,
+        &mut Health,
+        Option<&Player>,
+    )>::query()
+    .iter_mut(ecs)
+    // monsters already killed this round are on their way out
+    .filter(|(_, _, _, health, player)| health.current > 0 || player.is_some())
+    .for_each(|(entity, name, effects, health, player)| {
+        effects.0.iter_mut().for_each(|effect| {
+            match effect.kind {
+                StatusKind::Poison => {
+                    health.current -= POISON_DAMAGE;
+                    log.push(GameEvent::Attack {
+                        attacker: "poison".to_string(),
+                        victim: name.0.clone(),
+                        damage: POISON_DAMAGE,
+                    });
+                }
+                StatusKind::Regeneration => {
+                    health.current = i32::min(health.max, health.current + REGENERATION_HEAL);
+                }
+                StatusKind::Confusion
+                | StatusKind::Slow
+                | StatusKind::Haste
+                | StatusKind::Fear
+                | StatusKind::Telepathy => {}
+            }
+            effect.turns -= 1;
+            if effect.turns < 1 {
+                log.push(GameEvent::StatusEnded {
+                    target: name.0.clone(),
+                    status: effect.kind,
+                });
+            }
+        });
+        effects.0.retain(|effect| effect.turns > 0);
+
+        if health.current < 1 && player.is_none() {
+            killed.push(*entity);
+        }
+    });
+    // only the player's attacks poison monsters, so the kill is theirs
+    let player = player_entity(ecs);
+    killed.iter().for_each(|victim| {
+        kill(ecs, commands, log, summary, *victim, player, "poison");
+    });
+}
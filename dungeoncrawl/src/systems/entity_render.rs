@@ -5,17 +5,40 @@ use crate::prelude::*;
 #[read_component(Render)]
 #[read_component(FieldOfView)]
 #[read_component(Player)]
+#[read_component(Enemy)]
+#[read_component(StatusEffects)]
+#[read_component(Hidden)]
+#[read_component(Trap)]
 pub fn entity_render(ecs: &SubWorld, #[resource] camera: &Camera) {
-    let mut renderables = <(&Point, &Render)>::query();
+    let mut renderables =
+        <(&Point, &Render)>::query().filter(!component::<Hidden>() & !component::<Trap>());
     let mut fov = <&FieldOfView>::query().filter(component::<Player>());
     let mut draw_batch = DrawBatch::new();
     draw_batch.target(1);
     let offset = Point::new(camera.left_x, camera.top_y);
 
     let player_fov = fov.iter(ecs).nth(0).unwrap();
+    // telepathy shows monsters wherever they are
+    let telepathic = <&StatusEffects>::query()
+        .filter(component::<Player>())
+        .iter(ecs)
+        .any(|effects| effects.has(StatusKind::Telepathy));
+    if telepathic {
+        <(&Point, &Render)>::query()
+            .filter(component::<Enemy>())
+            .iter(ecs)
+            .filter(|(pos, _)| !player_fov.visible_tiles.contains(pos))
+            .for_each(|(pos, render)| {
+                draw_batch.set(*pos - offset, render.color, render.glyph);
+            });
+    }
 
-    renderables
+    // found traps go down first so anything standing on them is drawn over the top
+    let mut traps =
+        <(&Point, &Render)>::query().filter(component::<Trap>() & !component::<Hidden>());
+    traps
         .iter(ecs)
+        .chain(renderables.iter(ecs))
         .filter(|(pos, _)| player_fov.visible_tiles.contains(&pos))
         .for_each(|(pos, render)| {
             draw_batch.set(*pos - offset, render.color, render.glyph);
@@ -0,0 +1,83 @@
+use crate::prelude::*;
+
+#[system]
+#[read_component(Equip)]
+#[read_component(Unequip)]
+#[read_component(Equippable)]
+#[read_component(Equipped)]
+#[read_component(Name)]
+pub fn equipment(ecs: &SubWorld, commands: &mut CommandBuffer, #[resource] log: &mut GameLog) {
+    // (item, owner, slot) for everything worn, kept up to date as messages are handled
+    let mut worn: Vec<(Entity, Entity, EquipmentSlot)> = <(Entity, &Equipped)>::query()
+        .iter(ecs)
+        .map(|(item, equipped)| (*item, equipped.owner, equipped.slot))
+        .collect();
+
+    <(Entity, &Unequip)>::query()
+        .iter(ecs)
+        .for_each(|(message, unequip)| {
+            if let Some(idx) = worn.iter().position(|(item, _, _)| *item == unequip.item) {
+                worn.remove(idx);
+                commands.remove_component::<Equipped>(unequip.item);
+                log.push(GameEvent::Unequipped {
+                    item: item_name(ecs, unequip.item),
+                });
+            }
+            commands.remove(*message);
+        });
+
+    <(Entity, &Equip)>::query()
+        .iter(ecs)
+        .for_each(|(message, equip)| {
+            let equippable = ecs
+                .entry_ref(equip.item)
+                .ok()
+                .and_then(|item| item.get_component::<Equippable>().ok().cloned());
+            let already_worn = worn.iter().any(|(item, _, _)| *item == equip.item);
+            if let (Some(equippable), false) = (equippable, already_worn) {
+                // take the first free slot, otherwise swap out whatever is in the first one
+                let slots = equippable.0.slots();
+                let slot = *slots
+                    .iter()
+                    .find(|slot| {
+                        !worn.iter().any(|(_, owner, worn_slot)| {
+                            *owner == equip.owner && worn_slot == *slot
+                        })
+                    })
+                    .unwrap_or(&slots[0]);
+
+                worn.retain(|(item, owner, worn_slot)| {
+                    let displaced = *owner == equip.owner && *worn_slot == slot;
+                    if displaced {
+                        commands.remove_component::<Equipped>(*item);
+                        log.push(GameEvent::Unequipped {
+                            item: item_name(ecs, *item),
+                        });
+                    }
+                    !displaced
+                });
+
+                worn.push((equip.item, equip.owner, slot));
+                commands.add_component(
+                    equip.item,
+                    Equipped {
+                        owner: equip.owner,
+                        slot,
+                    },
+                );
+                log.push(GameEvent::Equipped {
+                    item: item_name(ecs, equip.item),
+                    slot,
+                });
+            }
+            commands.remove(*message);
+        });
+}
+
+fn item_name(ecs: &SubWorld, item: Entity) -> String {
+    ecs.entry_ref(item)
+        .ok()
+        .and_then(|item| item.get_component::<Name>().ok().cloned())
+        .map(|name| name.0)
+        .unwrap_or_default()
+}
@@ -0,0 +1,62 @@
+use super::chasing::{flee, is_afraid, mobility_of, move_or_attack, overridden, PlayerDistances};
+use crate::prelude::*;
+
+#[system]
+#[read_component(Point)]
+#[read_component(Guarding)]
+#[read_component(FieldOfView)]
+#[read_component(Health)]
+#[read_component(Player)]
+#[read_component(StatusEffects)]
+#[read_component(Energy)]
+#[read_component(Acted)]
+#[read_component(FleesWhenHurt)]
+#[read_component(Sleeping)]
+#[read_component(OpensDoors)]
+#[read_component(Swims)]
+pub fn guard(#[resource] map: &Map, ecs: &SubWorld, commands: &mut CommandBuffer) {
+    let mut guards =
+        <(Entity, &Point, &Guarding, &FieldOfView)>::query().filter(!component::<Sleeping>());
+    let player_pos = *<&Point>::query()
+        .filter(component::<Player>())
+        .iter(ecs)
+        .next()
+        .unwrap();
+
+    let mut distances = PlayerDistances::new(player_pos);
+
+    guards.iter(ecs).for_each(|(entity, pos, guarding, fov)| {
+        if !can_act(ecs, *entity) || overridden(ecs, *entity) {
+            return;
+        }
+        let seen = fov.visible_tiles.contains(&player_pos);
+        let mobility = mobility_of(ecs, *entity);
+        if seen && is_afraid(ecs, *entity) {
+            flee(
+                distances.get(map, mobility),
+                &map.pathing(mobility),
+                ecs,
+                commands,
+                *entity,
+                map.point2d_to_index(*pos),
+            );
+            return;
+        }
+        // intruders near the post get attacked, otherwise head back to it
+        let intruder = seen
+            && DistanceAlg::Pythagoras.distance2d(guarding.post, player_pos)
+                <= guarding.radius as f32;
+        let target = if intruder { player_pos } else { guarding.post };
+        if *pos == target {
+            return;
+        }
+        let step = if intruder {
+            distances.next_step(map, mobility, *pos)
+        } else {
+            map.pathing(mobility).next_step(*pos, target)
+        };
+        if let Some(destination) = step {
+            move_or_attack(ecs, commands, *entity, destination);
+        }
+    });
+}
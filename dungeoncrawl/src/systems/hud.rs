@@ -1,19 +1,38 @@
 use crate::prelude::*;
 
+const BOSS_BAR_WIDTH: i32 = 60;
+
 #[system]
 #[read_component(Health)]
+#[read_component(Experience)]
+#[read_component(StatusEffects)]
 #[read_component(Player)]
 #[read_component(Item)]
 #[read_component(Carried)]
 #[read_component(Name)]
-pub fn hud(ecs: &SubWorld) {
+#[read_component(Equipped)]
+#[read_component(Equippable)]
+#[read_component(Point)]
+#[read_component(FieldOfView)]
+#[read_component(Boss)]
+pub fn hud(
+    ecs: &SubWorld,
+    #[resource] seed: &Seed,
+    #[resource] log: &GameLog,
+    #[resource] map: &Map,
+    #[resource] progression: &Progression,
+) {
     let mut health_query = <&Health>::query().filter(component::<Player>());
 
     let player_health = health_query.iter(ecs).nth(0).unwrap();
 
     let mut draw_batch = DrawBatch::new();
     draw_batch.target(2);
-    draw_batch.print_centered(1, "Explore the Dungeon. Cursor keys to move.");
+    let hint = match map.movement {
+        Movement::FourWay => "Explore the Dungeon. Cursor keys to move, F to shoot, T to throw, C to close a door, S to search, I for inventory, L to read the log.",
+        Movement::EightWay => "Explore the Dungeon. Numpad or hjklyubn to move, F to shoot, T to throw, C to close a door, S to search, I for inventory, M to read the log.",
+    };
+    draw_batch.print_centered(1, hint);
     draw_batch.bar_horizontal(
         Point::zero(),
         SCREEN_WIDTH * 2,
@@ -30,6 +49,30 @@ pub fn hud(ecs: &SubWorld) {
         ColorPair::new(WHITE, RED),
     );
 
+    if let Some(experience) = <&Experience>::query()
+        .filter(component::<Player>())
+        .iter(ecs)
+        .next()
+    {
+        draw_batch.print_color(
+            Point::zero(),
+            format!(" Level {} ", experience.level),
+            ColorPair::new(YELLOW, BLACK),
+        );
+        draw_batch.print_color_right(
+            Point::new(SCREEN_WIDTH * 2, 0),
+            format!(" XP: {} / {} ", experience.current, experience.next_level()),
+            ColorPair::new(YELLOW, BLACK),
+        );
+        if experience.notice_turns > 0 {
+            draw_batch.print_color_centered(
+                3,
+                format!("Level up! You are now level {}.", experience.level),
+                ColorPair::new(GOLD, BLACK),
+            );
+        }
+    }
+
     let (player, map_level) = <(Entity, &Player)>::query()
         .iter(ecs)
         .find_map(|(entity, player)| Some((*entity, player.map_level)))
@@ -37,19 +80,39 @@ pub fn hud(ecs: &SubWorld) {
 
     draw_batch.print_color_right(
         Point::new(SCREEN_WIDTH * 2, 1),
-        format!("Dungeon Level: {}", map_level + 1),
+        format!(
+            "{}: Depth {} of {}",
+            progression.level(map_level).name,
+            map_level + 1,
+            progression.levels.len()
+        ),
         ColorPair::new(YELLOW, BLACK),
     );
+    draw_batch.print_color_right(
+        Point::new(SCREEN_WIDTH * 2, 2),
+        format!("Seed: {}", seed.0),
+        ColorPair::new(GRAY, BLACK),
+    );
 
-    let mut item_query = <(&Item, &Name, &Carried)>::query();
+    // only the first nine stacks have number-key shortcuts, the rest live on the inventory screen
+    let stacks = carried_stacks(ecs, player);
     let mut y = 3;
-    item_query
-        .iter(ecs)
-        .filter(|(_, _, carried)| carried.0 == player)
-        .for_each(|(_, name, _)| {
-            draw_batch.print(Point::new(3, y), format!("{} : {}", y - 2, &name.0));
-            y += 1;
-        });
+    stacks.iter().take(9).for_each(|stack| {
+        let line = format!("{} : {}", y - 2, stack.label());
+        if stack.equipped.is_some() {
+            draw_batch.print_color(Point::new(3, y), line, ColorPair::new(CYAN, BLACK));
+        } else {
+            draw_batch.print(Point::new(3, y), line);
+        }
+        y += 1;
+    });
+    if stacks.len() > 9 {
+        draw_batch.print_color(
+            Point::new(3, y),
+            format!("... and {} more (I)", stacks.len() - 9),
+            ColorPair::new(GRAY, BLACK),
+        );
+    }
     if y > 3 {
         draw_batch.print_color(
             Point::new(3, 2),
@@ -57,5 +120,86 @@ pub fn hud(ecs: &SubWorld) {
             ColorPair::new(YELLOW, BLACK),
         );
     }
+
+    draw_batch.print_color_right(
+        Point::new(SCREEN_WIDTH * 2, 4),
+        "Equipment",
+        ColorPair::new(YELLOW, BLACK),
+    );
+    let mut worn_query = <(&Equipped, &Name)>::query();
+    EquipmentSlot::ALL
+        .iter()
+        .enumerate()
+        .for_each(|(row, slot)| {
+            let worn = worn_query
+                .iter(ecs)
+                .find(|(equipped, _)| equipped.owner == player && equipped.slot == *slot)
+                .map(|(_, name)| name.0.clone())
+                .unwrap_or_else(|| "-".to_string());
+            draw_batch.print_right(
+                Point::new(SCREEN_WIDTH * 2, 5 + row as i32),
+                format!("{}: {}", slot.label(), worn),
+            );
+        });
+
+    if let Some(effects) = <&StatusEffects>::query()
+        .filter(component::<Player>())
+        .iter(ecs)
+        .next()
+        .filter(|effects| !effects.0.is_empty())
+    {
+        draw_batch.print_color_right(
+            Point::new(SCREEN_WIDTH * 2, 12),
+            "Status",
+            ColorPair::new(YELLOW, BLACK),
+        );
+        effects.0.iter().enumerate().for_each(|(row, effect)| {
+            draw_batch.print_color_right(
+                Point::new(SCREEN_WIDTH * 2, 13 + row as i32),
+                format!("{} ({})", effect.kind.label(), effect.turns),
+                ColorPair::new(effect.kind.color(), BLACK),
+            );
+        });
+    }
+
+    let log_top = SCREEN_HEIGHT * 2 - HUD_LOG_LINES as i32 - 1;
+
+    // every boss in view gets a bar of its own just above the log
+    let player_fov = <&FieldOfView>::query()
+        .filter(component::<Player>())
+        .iter(ecs)
+        .next()
+        .unwrap();
+    <(&Point, &Name, &Health)>::query()
+        .filter(component::<Boss>())
+        .iter(ecs)
+        .filter(|(pos, _, _)| player_fov.visible_tiles.contains(pos))
+        .enumerate()
+        .for_each(|(row, (_, name, health))| {
+            let y = log_top - 2 - row as i32;
+            draw_batch.bar_horizontal(
+                Point::new(SCREEN_WIDTH - BOSS_BAR_WIDTH / 2, y),
+                BOSS_BAR_WIDTH,
+                health.current,
+                health.max,
+                ColorPair::new(PURPLE, BLACK),
+            );
+            draw_batch.print_color_centered(
+                y,
+                format!(" {}: {} / {} ", name.0, health.current, health.max),
+                ColorPair::new(WHITE, PURPLE),
+            );
+        });
+
+    log.recent(HUD_LOG_LINES)
+        .iter()
+        .enumerate()
+        .for_each(|(y, event)| {
+            draw_batch.print_color(
+                Point::new(1, log_top + y as i32),
+                event.text(),
+                event.color(),
+            );
+        });
     draw_batch.submit(10000).expect("Batch error");
 }
@@ -0,0 +1,83 @@
+use crate::prelude::*;
+
+#[system]
+#[read_component(Point)]
+#[read_component(Player)]
+#[read_component(Item)]
+#[read_component(Carried)]
+#[read_component(Name)]
+#[read_component(Equippable)]
+#[read_component(Equipped)]
+#[read_component(Throwable)]
+#[read_component(Provides)]
+#[read_component(Ranged)]
+#[read_component(Enemy)]
+#[read_component(FieldOfView)]
+#[allow(clippy::too_many_arguments)]
+pub fn inventory_input(
+    ecs: &SubWorld,
+    commands: &mut CommandBuffer,
+    #[resource] key: &Option<VirtualKeyCode>,
+    #[resource] turn_state: &mut TurnState,
+    #[resource] cursor: &mut InventoryCursor,
+    #[resource] log: &mut GameLog,
+    #[resource] map: &Map,
+    #[resource] targeting: &mut Targeting,
+) {
+    if let Some(key) = key {
+        let (player, player_pos) = <(Entity, &Point)>::query()
+            .filter(component::<Player>())
+            .iter(ecs)
+            .map(|(entity, pos)| (*entity, *pos))
+            .next()
+            .unwrap();
+        let stacks = carried_stacks(ecs, player);
+        let selected = stacks.get(cursor.0);
+
+        match key {
+            VirtualKeyCode::Up => cursor.0 = cursor.0.saturating_sub(1),
+            VirtualKeyCode::Down => cursor.0 += 1,
+            VirtualKeyCode::Escape | VirtualKeyCode::I => *turn_state = TurnState::AwaitingInput,
+            VirtualKeyCode::Return | VirtualKeyCode::T
+                if selected.is_some_and(|stack| needs_target(ecs, stack.items[0])) =>
+            {
+                let item = selected.unwrap().items[0];
+                start_targeting(ecs, player, item, map, targeting, turn_state);
+            }
+            VirtualKeyCode::Return => {
+                if let Some(stack) = selected {
+                    use_item(player, stack.items[0], ecs, commands);
+                    *turn_state = TurnState::PlayerTurn;
+                }
+            }
+            VirtualKeyCode::U => {
+                if let Some(stack) =
+                    selected.filter(|stack| stack.stackable && !needs_target(ecs, stack.items[0]))
+                {
+                    use_item(player, stack.items[0], ecs, commands);
+                    *turn_state = TurnState::PlayerTurn;
+                }
+            }
+            VirtualKeyCode::E => {
+                if let Some(stack) = selected.filter(|stack| !stack.stackable) {
+                    use_item(player, stack.items[0], ecs, commands);
+                    *turn_state = TurnState::PlayerTurn;
+                }
+            }
+            VirtualKeyCode::D => {
+                if let Some(stack) = selected {
+                    let item = stack.items[0];
+                    commands.remove_component::<Carried>(item);
+                    commands.remove_component::<Equipped>(item);
+                    commands.add_component(item, player_pos);
+                    log.push(GameEvent::Dropped {
+                        item: stack.name.clone(),
+                    });
+                    *turn_state = TurnState::PlayerTurn;
+                }
+            }
+            _ => {}
+        }
+        cursor.0 = usize::min(cursor.0, stacks.len().saturating_sub(1));
+    }
+}
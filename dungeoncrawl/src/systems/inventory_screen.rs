@@ -0,0 +1,107 @@
+use crate::prelude::*;
+use legion::world::EntryRef;
+
+#[system]
+#[read_component(Player)]
+#[read_component(Item)]
+#[read_component(Carried)]
+#[read_component(Name)]
+#[read_component(Description)]
+#[read_component(Equippable)]
+#[read_component(Equipped)]
+#[read_component(Damage)]
+#[read_component(Defense)]
+#[read_component(Provides)]
+#[read_component(Ranged)]
+#[read_component(Throwable)]
+pub fn inventory_screen(ecs: &SubWorld, #[resource] cursor: &InventoryCursor) {
+    let player = <Entity>::query()
+        .filter(component::<Player>())
+        .iter(ecs)
+        .cloned()
+        .next()
+        .unwrap();
+    let stacks = carried_stacks(ecs, player);
+
+    let mut draw_batch = DrawBatch::new();
+    draw_batch.target(2);
+    draw_batch.print_color_centered(1, "Inventory", ColorPair::new(YELLOW, BLACK));
+    draw_batch.print_color_centered(
+        2,
+        "Up/Down to select, Enter to use or equip, U to use, E to equip, T to throw, D to drop, Escape to close.",
+        ColorPair::new(GRAY, BLACK),
+    );
+
+    if stacks.is_empty() {
+        draw_batch.print(Point::new(3, 4), "You aren't carrying anything.");
+    }
+
+    stacks.iter().enumerate().for_each(|(row, stack)| {
+        let y = 4 + row as i32;
+        if row == cursor.0 {
+            draw_batch.print_color(
+                Point::new(1, y),
+                format!("> {}", stack.label()),
+                ColorPair::new(YELLOW, BLACK),
+            );
+        } else if stack.equipped.is_some() {
+            draw_batch.print_color(Point::new(3, y), stack.label(), ColorPair::new(CYAN, BLACK));
+        } else {
+            draw_batch.print(Point::new(3, y), stack.label());
+        }
+    });
+
+    if let Some(stack) = stacks.get(cursor.0) {
+        let entry = ecs.entry_ref(stack.items[0]).unwrap();
+        draw_batch.print_color(
+            Point::new(SCREEN_WIDTH, 4),
+            &stack.name,
+            ColorPair::new(YELLOW, BLACK),
+        );
+        item_details(&entry)
+            .iter()
+            .enumerate()
+            .for_each(|(row, line)| {
+                draw_batch.print(Point::new(SCREEN_WIDTH, 6 + row as i32), line);
+            });
+    }
+
+    draw_batch.submit(10000).expect("Batch error");
+}
+
+fn item_details(entry: &EntryRef) -> Vec<String> {
+    let mut lines = Vec::new();
+    if let Ok(description) = entry.get_component::<Description>() {
+        lines.push(description.0.clone());
+        lines.push(String::new());
+    }
+    if let Ok(equippable) = entry.get_component::<Equippable>() {
+        let slots: Vec<&str> = equippable
+            .0
+            .slots()
+            .iter()
+            .map(|slot| slot.label())
+            .collect();
+        lines.push(format!("Worn in: {}", slots.join(" or ")));
+    }
+    if let Ok(damage) = entry.get_component::<Damage>() {
+        lines.push(format!("Damage: +{}", damage.0));
+    }
+    if let Ok(ranged) = entry.get_component::<Ranged>() {
+        if entry.get_component::<Throwable>().is_ok() {
+            lines.push(format!("Can be thrown up to {} tiles.", ranged.range));
+        } else {
+            lines.push(format!("Range: {} tiles", ranged.range));
+        }
+    }
+    if let Ok(defense) = entry.get_component::<Defense>() {
+        lines.push(format!("Defense: +{}", defense.0));
+    }
+    if let Ok(provides) = entry.get_component::<Provides>() {
+        provides
+            .0
+            .iter()
+            .for_each(|effect| lines.push(effect.describe()));
+    }
+    lines
+}
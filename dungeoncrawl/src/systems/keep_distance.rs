@@ -0,0 +1,68 @@
+use super::chasing::{flee, is_afraid, mobility_of, move_or_attack, overridden, PlayerDistances};
+use crate::prelude::*;
+
+#[system]
+#[read_component(Point)]
+#[read_component(KeepsDistance)]
+#[read_component(FieldOfView)]
+#[read_component(Health)]
+#[read_component(Player)]
+#[read_component(StatusEffects)]
+#[read_component(Energy)]
+#[read_component(Acted)]
+#[read_component(FleesWhenHurt)]
+#[read_component(Ranged)]
+#[read_component(Sleeping)]
+#[read_component(OpensDoors)]
+#[read_component(Swims)]
+pub fn keep_distance(#[resource] map: &Map, ecs: &SubWorld, commands: &mut CommandBuffer) {
+    let mut shooters =
+        <(Entity, &Point, &KeepsDistance, &FieldOfView)>::query().filter(!component::<Sleeping>());
+    let (player, player_pos) = <(Entity, &Point)>::query()
+        .filter(component::<Player>())
+        .iter(ecs)
+        .map(|(entity, pos)| (*entity, *pos))
+        .next()
+        .unwrap();
+
+    let mut distances = PlayerDistances::new(player_pos);
+
+    shooters
+        .iter(ecs)
+        .for_each(|(entity, pos, keeps_distance, fov)| {
+            if !fov.visible_tiles.contains(&player_pos)
+                || !can_act(ecs, *entity)
+                || overridden(ecs, *entity)
+            {
+                return;
+            }
+            // back off first; a cornered shooter stands and fights
+            let distance = DistanceAlg::Pythagoras.distance2d(*pos, player_pos);
+            let mobility = mobility_of(ecs, *entity);
+            if (is_afraid(ecs, *entity) || distance < keeps_distance.min as f32)
+                && flee(
+                    distances.get(map, mobility),
+                    &map.pathing(mobility),
+                    ecs,
+                    commands,
+                    *entity,
+                    map.point2d_to_index(*pos),
+                )
+            {
+                return;
+            }
+            // the monster is its own weapon, so its Ranged component sets the reach
+            if in_range(map, *pos, player_pos, weapon_range(ecs, *entity)) {
+                commands.push((
+                    (),
+                    WantsToAttack {
+                        attacker: *entity,
+                        victim: player,
+                        ranged: Some(*entity),
+                    },
+                ));
+            } else if let Some(destination) = distances.next_step(map, mobility, *pos) {
+                move_or_attack(ecs, commands, *entity, destination);
+            }
+        });
+}
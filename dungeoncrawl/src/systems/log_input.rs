@@ -0,0 +1,25 @@
+use crate::prelude::*;
+
+#[system]
+pub fn log_input(
+    #[resource] key: &Option<VirtualKeyCode>,
+    #[resource] log: &mut GameLog,
+    #[resource] turn_state: &mut TurnState,
+) {
+    if let Some(key) = key {
+        match key {
+            VirtualKeyCode::Up => log.scroll += 1,
+            VirtualKeyCode::Down => log.scroll = log.scroll.saturating_sub(1),
+            VirtualKeyCode::PageUp => log.scroll += LOG_PAGE_LINES,
+            VirtualKeyCode::PageDown => log.scroll = log.scroll.saturating_sub(LOG_PAGE_LINES),
+            VirtualKeyCode::Home => log.scroll = log.max_scroll(),
+            VirtualKeyCode::End => log.scroll = 0,
+            VirtualKeyCode::Escape | VirtualKeyCode::L | VirtualKeyCode::M => {
+                log.scroll = 0;
+                *turn_state = TurnState::AwaitingInput;
+            }
+            _ => {}
+        }
+        log.scroll = usize::min(log.scroll, log.max_scroll());
+    }
+}
@@ -0,0 +1,23 @@
+use crate::prelude::*;
+
+#[system]
+pub fn log_viewer(#[resource] log: &GameLog) {
+    let mut draw_batch = DrawBatch::new();
+    draw_batch.target(2);
+    draw_batch.print_color_centered(1, "Message Log", ColorPair::new(YELLOW, BLACK));
+    draw_batch.print_color_centered(
+        2,
+        "Up/Down and PageUp/PageDown to scroll, Home/End to jump, Escape to close.",
+        ColorPair::new(GRAY, BLACK),
+    );
+
+    let end = log.entries.len() - log.scroll;
+    let start = end.saturating_sub(LOG_PAGE_LINES);
+    log.entries[start..end]
+        .iter()
+        .enumerate()
+        .for_each(|(y, event)| {
+            draw_batch.print_color(Point::new(3, y as i32 + 4), event.text(), event.color());
+        });
+    draw_batch.submit(10000).expect("Batch error");
+}
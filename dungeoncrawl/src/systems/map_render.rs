@@ -19,12 +19,13 @@ pub fn map_render(
         for x in camera.left_x..camera.right_x {
             let pt = Point::new(x, y);
             let offset = Point::new(camera.left_x, camera.top_y);
-            let idx = map_idx(x, y);
-            if map.in_bounds(pt)
-                && (player_fov.visible_tiles.contains(&pt) | map.revealed_tiles[idx])
-            {
+            if !map.in_bounds(pt) {
+                continue;
+            }
+            let idx = map.point2d_to_index(pt);
+            if player_fov.visible_tiles.contains(&pt) | map.revealed_tiles[idx] {
                 let tint = if player_fov.visible_tiles.contains(&pt) {
-                    WHITE
+                    theme.tile_tint(map.tiles[idx])
                 } else {
                     DARK_GRAY
                 };
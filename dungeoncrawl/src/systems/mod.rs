@@ -2,47 +2,81 @@ mod chasing;
 mod combat;
 mod end_turn;
 mod entity_render;
+mod equipment;
 mod fov;
+mod guard;
 mod hud;
+mod inventory_input;
+mod inventory_screen;
+mod keep_distance;
+mod log_input;
+mod log_viewer;
 mod map_render;
 mod movement;
+mod patrol;
 mod player_input;
 mod random_move;
+mod specials;
+mod targeting_input;
+mod targeting_render;
 mod tooltips;
+mod traps;
 mod use_items;
+mod wake_up;
 use crate::prelude::*;
+use legion::systems::Builder;
 
-pub fn build_input_scheduler() -> Schedule {
-    Schedule::builder()
+// the render systems draw into bracket-lib's batches, so a headless run leaves them out
+fn add_render_systems(builder: &mut Builder, headless: bool) {
+    if !headless {
+        builder
+            .add_system(map_render::map_render_system())
+            .add_system(entity_render::entity_render_system())
+            .add_system(hud::hud_system());
+    }
+}
+
+pub fn build_input_scheduler(headless: bool) -> Schedule {
+    let mut builder = Schedule::builder();
+    builder
         .add_system(player_input::player_input_system())
         .add_system(fov::fov_system())
-        .flush()
-        .add_system(map_render::map_render_system())
-        .add_system(entity_render::entity_render_system())
-        .add_system(hud::hud_system())
-        .add_system(tooltips::tooltips_system())
-        .build()
+        .flush();
+    add_render_systems(&mut builder, headless);
+    if !headless {
+        builder.add_system(tooltips::tooltips_system());
+    }
+    builder.build()
 }
 
-pub fn build_player_scheduler() -> Schedule {
-    Schedule::builder()
+pub fn build_player_scheduler(headless: bool) -> Schedule {
+    let mut builder = Schedule::builder();
+    builder
         .add_system(use_items::use_items_system())
+        .add_system(equipment::equipment_system())
         .add_system(combat::combat_system())
         .flush()
         .add_system(movement::movement_system())
         .flush()
+        .add_system(traps::perception_system())
         .add_system(fov::fov_system())
-        .flush()
-        .add_system(map_render::map_render_system())
-        .add_system(entity_render::entity_render_system())
-        .add_system(hud::hud_system())
-        .add_system(end_turn::end_turn_system())
-        .build()
+        .flush();
+    add_render_systems(&mut builder, headless);
+    builder.add_system(end_turn::end_turn_system());
+    builder.build()
 }
-pub fn build_monster_scheduler() -> Schedule {
-    Schedule::builder()
+
+pub fn build_monster_scheduler(headless: bool) -> Schedule {
+    let mut builder = Schedule::builder();
+    builder
+        .add_system(wake_up::wake_up_system())
+        .add_system(specials::specials_system())
+        .flush()
         .add_system(random_move::random_move_system())
         .add_system(chasing::chasing_system())
+        .add_system(keep_distance::keep_distance_system())
+        .add_system(patrol::patrol_system())
+        .add_system(guard::guard_system())
         .flush()
         .add_system(use_items::use_items_system())
         .add_system(combat::combat_system())
@@ -50,10 +84,40 @@ pub fn build_monster_scheduler() -> Schedule {
         .add_system(movement::movement_system())
         .flush()
         .add_system(fov::fov_system())
-        .flush()
-        .add_system(map_render::map_render_system())
-        .add_system(entity_render::entity_render_system())
-        .add_system(hud::hud_system())
-        .add_system(end_turn::end_turn_system())
-        .build()
+        .flush();
+    add_render_systems(&mut builder, headless);
+    builder.add_system(end_turn::end_turn_system());
+    builder.build()
+}
+
+pub fn build_log_scheduler(headless: bool) -> Schedule {
+    let mut builder = Schedule::builder();
+    builder.add_system(log_input::log_input_system()).flush();
+    if !headless {
+        builder.add_system(log_viewer::log_viewer_system());
+    }
+    builder.build()
+}
+
+pub fn build_inventory_scheduler(headless: bool) -> Schedule {
+    let mut builder = Schedule::builder();
+    builder
+        .add_system(inventory_input::inventory_input_system())
+        .flush();
+    if !headless {
+        builder.add_system(inventory_screen::inventory_screen_system());
+    }
+    builder.build()
+}
+
+pub fn build_targeting_scheduler(headless: bool) -> Schedule {
+    let mut builder = Schedule::builder();
+    builder
+        .add_system(targeting_input::targeting_input_system())
+        .flush();
+    add_render_systems(&mut builder, headless);
+    if !headless {
+        builder.add_system(targeting_render::targeting_render_system());
+    }
+    builder.build()
 }
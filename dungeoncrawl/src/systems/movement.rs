@@ -1,17 +1,54 @@
+use super::combat::{kill, player_entity};
+use super::traps::spring_trap;
 use crate::prelude::*;
 
+const LAVA_DAMAGE: i32 = 3;
+
 #[system(for_each)]
 #[read_component(Player)]
 #[read_component(FieldOfView)]
+#[read_component(Name)]
+#[read_component(Point)]
+#[read_component(Trap)]
+#[read_component(Sleeping)]
+#[read_component(ChasingPlayer)]
+#[read_component(OpensDoors)]
+#[write_component(Health)]
+#[read_component(Boss)]
+#[read_component(ExperienceValue)]
+#[write_component(Experience)]
+#[write_component(Damage)]
+#[allow(clippy::too_many_arguments)]
 pub fn movement(
     entity: &Entity,
     want_move: &WantsToMove,
     #[resource] map: &mut Map,
     #[resource] camera: &mut Camera,
+    #[resource] log: &mut GameLog,
+    #[resource] rng: &mut RandomNumberGenerator,
+    #[resource] turn_state: &mut TurnState,
+    #[resource] summary: &mut RunSummary,
     ecs: &mut SubWorld,
     commands: &mut CommandBuffer,
 ) {
-    if map.can_enter_tile(want_move.destination) {
+    let player = ecs
+        .entry_ref(want_move.entity)
+        .is_ok_and(|entry| entry.get_component::<Player>().is_ok());
+    // walking into a closed door opens it instead, for those that can; for the rest it's a wall
+    if map.is_closed_door(want_move.destination) {
+        let opens_doors = player
+            || ecs
+                .entry_ref(want_move.entity)
+                .is_ok_and(|entry| entry.get_component::<OpensDoors>().is_ok());
+        if opens_doors {
+            let idx = map.point2d_to_index(want_move.destination);
+            map.tiles[idx] = TileType::Door { open: true };
+            refresh_views(ecs, commands);
+            if player {
+                log.push(GameEvent::OpenedDoor);
+            }
+        }
+    } else if map.can_enter_tile(want_move.destination) {
         commands.add_component(want_move.entity, want_move.destination);
 
         if let Ok(entry) = ecs.entry_ref(want_move.entity) {
@@ -20,11 +57,76 @@ pub fn movement(
                 if entry.get_component::<Player>().is_ok() {
                     camera.on_player_move(want_move.destination);
                     fov.visible_tiles.iter().for_each(|pos| {
-                        map.revealed_tiles[map_idx(pos.x, pos.y)] = true;
+                        let idx = map.point2d_to_index(*pos);
+                        map.revealed_tiles[idx] = true;
                     });
                 }
             }
         }
+
+        // stairs only work when stepped onto, so arriving on them doesn't send the player straight back
+        match map.tiles[map.point2d_to_index(want_move.destination)] {
+            TileType::Lava => burn(ecs, commands, log, summary, want_move.entity),
+            TileType::Chasm if player => {
+                log.push(GameEvent::FellIntoChasm);
+                *turn_state = TurnState::NextLevel;
+            }
+            TileType::Exit if player => *turn_state = TurnState::NextLevel,
+            TileType::UpStairs if player => *turn_state = TurnState::PreviousLevel,
+            _ => {}
+        }
+
+        if player {
+            spring_trap(
+                ecs,
+                commands,
+                map,
+                camera,
+                rng,
+                log,
+                want_move.entity,
+                want_move.destination,
+            );
+        }
     }
     commands.remove(*entity);
 }
+
+// lava hurts whatever wanders into it
+fn burn(
+    ecs: &mut SubWorld,
+    commands: &mut CommandBuffer,
+    log: &mut GameLog,
+    summary: &mut RunSummary,
+    entity: Entity,
+) {
+    let mut killed = false;
+    if let Ok(mut entry) = ecs.entry_mut(entity) {
+        let name = match entry.get_component::<Name>() {
+            Ok(name) => name.0.clone(),
+            Err(_) => return,
+        };
+        let player = entry.get_component::<Player>().is_ok();
+        if let Ok(health) = entry.get_component_mut::<Health>() {
+            health.current -= LAVA_DAMAGE;
+            log.push(GameEvent::Attack {
+                attacker: "lava".to_string(),
+                victim: name,
+                damage: LAVA_DAMAGE,
+            });
+            killed = health.current < 1 && !player;
+        }
+    }
+    // monsters only blunder into lava while busy with the player, who gets the credit
+    if killed {
+        let player = player_entity(ecs);
+        kill(ecs, commands, log, summary, entity, player, "lava");
+    }
+}
+
+// opening or closing a door changes what everyone can see
+pub fn refresh_views(ecs: &SubWorld, commands: &mut CommandBuffer) {
+    <(Entity, &FieldOfView)>::query()
+        .iter(ecs)
+        .for_each(|(entity, fov)| commands.add_component(*entity, fov.clone_dirty()));
+}
@@ -0,0 +1,53 @@
+use super::chasing::{mobility_of, move_or_attack, overridden};
+use crate::prelude::*;
+
+#[system]
+#[read_component(Point)]
+#[write_component(Patrolling)]
+#[read_component(FieldOfView)]
+#[read_component(Health)]
+#[read_component(Player)]
+#[read_component(StatusEffects)]
+#[read_component(Energy)]
+#[read_component(Acted)]
+#[read_component(Sleeping)]
+#[read_component(LastSeenPlayer)]
+#[read_component(OpensDoors)]
+#[read_component(Swims)]
+pub fn patrol(#[resource] map: &Map, ecs: &mut SubWorld, commands: &mut CommandBuffer) {
+    let player_pos = *<&Point>::query()
+        .filter(component::<Player>())
+        .iter(ecs)
+        .next()
+        .unwrap();
+
+    // once the player is in sight, chasing takes over until the monster gives up searching
+    let walkers: Vec<(Entity, Point)> = <(Entity, &Point, &Patrolling, &FieldOfView)>::query()
+        .filter(!component::<Sleeping>() & !component::<LastSeenPlayer>())
+        .iter(ecs)
+        .filter(|(_, _, patrol, fov)| {
+            !patrol.waypoints.is_empty() && !fov.visible_tiles.contains(&player_pos)
+        })
+        .map(|(entity, pos, _, _)| (*entity, *pos))
+        .collect();
+
+    walkers.iter().for_each(|(entity, pos)| {
+        if !can_act(ecs, *entity) || overridden(ecs, *entity) {
+            return;
+        }
+        let waypoint = {
+            let mut entry = ecs.entry_mut(*entity).unwrap();
+            let patrol = entry.get_component_mut::<Patrolling>().unwrap();
+            if patrol.waypoints[patrol.next] == *pos {
+                patrol.next = (patrol.next + 1) % patrol.waypoints.len();
+            }
+            patrol.waypoints[patrol.next]
+        };
+        if let Some(destination) = map
+            .pathing(mobility_of(ecs, *entity))
+            .next_step(*pos, waypoint)
+        {
+            move_or_attack(ecs, commands, *entity, destination);
+        }
+    });
+}
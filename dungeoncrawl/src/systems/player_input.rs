@@ -1,3 +1,6 @@
+use super::movement::refresh_views;
+use super::random_move::random_direction;
+use super::traps::search;
 use crate::prelude::*;
 
 #[system]
@@ -7,26 +10,56 @@ use crate::prelude::*;
 #[write_component(Health)]
 #[read_component(Item)]
 #[read_component(Carried)]
-#[read_component(Weapon)]
+#[read_component(Equippable)]
+#[read_component(Equipped)]
+#[read_component(Name)]
+#[read_component(StatusEffects)]
+#[read_component(FieldOfView)]
+#[read_component(Ranged)]
+#[read_component(Throwable)]
+#[read_component(Provides)]
+#[read_component(Hidden)]
+#[read_component(SecretDoor)]
+#[allow(clippy::too_many_arguments)]
 pub fn player_input(
     ecs: &mut SubWorld,
     commands: &mut CommandBuffer,
     #[resource] key: &Option<VirtualKeyCode>,
     #[resource] turn_state: &mut TurnState,
+    #[resource] log: &mut GameLog,
+    #[resource] rng: &mut RandomNumberGenerator,
+    #[resource] map: &mut Map,
+    #[resource] targeting: &mut Targeting,
 ) {
     let mut players = <(Entity, &Point)>::query().filter(component::<Player>());
+    let eight_way = map.movement == Movement::EightWay;
     if let Some(key) = key {
         let delta = match key {
             VirtualKeyCode::Left => Point::new(-1, 0),
             VirtualKeyCode::Right => Point::new(1, 0),
             VirtualKeyCode::Up => Point::new(0, -1),
             VirtualKeyCode::Down => Point::new(0, 1),
+            // numpad and vi-keys only move in eight-way mode, so L still opens the log otherwise
+            VirtualKeyCode::Numpad4 | VirtualKeyCode::H if eight_way => Point::new(-1, 0),
+            VirtualKeyCode::Numpad6 | VirtualKeyCode::L if eight_way => Point::new(1, 0),
+            VirtualKeyCode::Numpad8 | VirtualKeyCode::K if eight_way => Point::new(0, -1),
+            VirtualKeyCode::Numpad2 | VirtualKeyCode::J if eight_way => Point::new(0, 1),
+            VirtualKeyCode::Numpad7 | VirtualKeyCode::Y if eight_way => Point::new(-1, -1),
+            VirtualKeyCode::Numpad9 | VirtualKeyCode::U if eight_way => Point::new(1, -1),
+            VirtualKeyCode::Numpad1 | VirtualKeyCode::B if eight_way => Point::new(-1, 1),
+            VirtualKeyCode::Numpad3 | VirtualKeyCode::N if eight_way => Point::new(1, 1),
             VirtualKeyCode::G => {
                 let (player, player_pos) = players
                     .iter(ecs)
                     .find_map(|(entity, pos)| Some((*entity, *pos)))
                     .unwrap();
 
+                let mut worn_slots: Vec<EquipmentSlot> = <&Equipped>::query()
+                    .iter(ecs)
+                    .filter(|equipped| equipped.owner == player)
+                    .map(|equipped| equipped.slot)
+                    .collect();
+
                 let mut items = <(Entity, &Item, &Point)>::query();
                 items
                     .iter(ecs)
@@ -36,40 +69,136 @@ pub fn player_input(
                         commands.add_component(*entity, Carried(player));
 
                         if let Ok(e) = ecs.entry_ref(*entity) {
-                            if e.get_component::<Weapon>().is_ok() {
-                                <(Entity, &Carried, &Weapon)>::query()
-                                    .iter(ecs)
-                                    .filter(|(_, c, _)| c.0 == player)
-                                    .for_each(|(e, _, _)| {
-                                        commands.remove(*e);
-                                    })
+                            let item_name = e.get_component::<Name>().unwrap().0.clone();
+                            // put new gear on straight away if there's a free slot for it
+                            if let Ok(equippable) = e.get_component::<Equippable>() {
+                                let free_slot = equippable
+                                    .0
+                                    .slots()
+                                    .iter()
+                                    .find(|slot| !worn_slots.contains(slot));
+                                if let Some(slot) = free_slot {
+                                    worn_slots.push(*slot);
+                                    commands.push((
+                                        (),
+                                        Equip {
+                                            owner: player,
+                                            item: *entity,
+                                        },
+                                    ));
+                                }
                             }
+                            log.push(GameEvent::PickedUp { item: item_name });
                         }
                     });
                 Point::new(0, 0)
             }
-            VirtualKeyCode::Key1 => use_item(0, ecs, commands),
-            VirtualKeyCode::Key2 => use_item(1, ecs, commands),
-            VirtualKeyCode::Key3 => use_item(2, ecs, commands),
-            VirtualKeyCode::Key4 => use_item(3, ecs, commands),
-            VirtualKeyCode::Key5 => use_item(4, ecs, commands),
-            VirtualKeyCode::Key6 => use_item(5, ecs, commands),
-            VirtualKeyCode::Key7 => use_item(6, ecs, commands),
-            VirtualKeyCode::Key8 => use_item(7, ecs, commands),
-            VirtualKeyCode::Key9 => use_item(8, ecs, commands),
+            VirtualKeyCode::L | VirtualKeyCode::M => {
+                // reading the log doesn't cost a turn
+                *turn_state = TurnState::ViewingLog;
+                return;
+            }
+            VirtualKeyCode::I => {
+                *turn_state = TurnState::Inventory;
+                return;
+            }
+            VirtualKeyCode::C => {
+                let (_, player_pos) = players.iter(ecs).next().unwrap();
+                let player_pos = *player_pos;
+                if !close_door(ecs, commands, map, player_pos) {
+                    log.push(GameEvent::NoDoorToClose);
+                    return;
+                }
+                log.push(GameEvent::ClosedDoor);
+                Point::zero()
+            }
+            VirtualKeyCode::S => {
+                let (_, player_pos) = players.iter(ecs).next().unwrap();
+                let player_pos = *player_pos;
+                if !search(ecs, commands, map, log, player_pos) {
+                    log.push(GameEvent::FoundNothing);
+                }
+                Point::zero()
+            }
+            VirtualKeyCode::F => {
+                // fire whatever ranged weapon is in hand
+                let (player, _) = players.iter(ecs).next().unwrap();
+                let weapon = <(Entity, &Equipped, &Ranged)>::query()
+                    .iter(ecs)
+                    .filter(|(_, equipped, _)| equipped.owner == *player)
+                    .map(|(entity, _, _)| *entity)
+                    .next();
+                match weapon {
+                    Some(weapon) => {
+                        start_targeting(ecs, *player, weapon, map, targeting, turn_state)
+                    }
+                    None => log.push(GameEvent::NoRangedWeapon),
+                }
+                return;
+            }
+            VirtualKeyCode::T => {
+                let (player, _) = players.iter(ecs).next().unwrap();
+                let thrown = <(Entity, &Carried)>::query()
+                    .filter(component::<Throwable>())
+                    .iter(ecs)
+                    .filter(|(_, carried)| carried.0 == *player)
+                    .map(|(entity, _)| *entity)
+                    .next();
+                match thrown {
+                    Some(thrown) => {
+                        start_targeting(ecs, *player, thrown, map, targeting, turn_state)
+                    }
+                    None => log.push(GameEvent::NothingToThrow),
+                }
+                return;
+            }
+            VirtualKeyCode::Key1
+            | VirtualKeyCode::Key2
+            | VirtualKeyCode::Key3
+            | VirtualKeyCode::Key4
+            | VirtualKeyCode::Key5
+            | VirtualKeyCode::Key6
+            | VirtualKeyCode::Key7
+            | VirtualKeyCode::Key8
+            | VirtualKeyCode::Key9 => {
+                let (player, _) = players.iter(ecs).next().unwrap();
+                let n = *key as usize - VirtualKeyCode::Key1 as usize;
+                if let Some(stack) = carried_stacks(ecs, *player).get(n) {
+                    let item = stack.items[0];
+                    // throwing and aimed scrolls need a target first, so they don't end the turn yet
+                    if needs_target(ecs, item) {
+                        start_targeting(ecs, *player, item, map, targeting, turn_state);
+                        return;
+                    }
+                    use_item(*player, item, ecs, commands);
+                }
+                Point::zero()
+            }
             _ => Point::new(0, 0),
         };
 
+        // a confused player stumbles in a random direction instead
+        let confused = <&StatusEffects>::query()
+            .filter(component::<Player>())
+            .iter(ecs)
+            .any(|effects| effects.has(StatusKind::Confusion));
+        let delta = if confused && delta != Point::zero() {
+            random_direction(rng, map.movement)
+        } else {
+            delta
+        };
+
         // get player entity and destination destructured
-        let (player_entity, destination) = players
+        let (player_entity, player_pos, destination) = players
             .iter(ecs)
-            .find_map(|(entity, pos)| Some((*entity, *pos + delta)))
+            .find_map(|(entity, pos)| Some((*entity, *pos, *pos + delta)))
             .unwrap();
 
         // ensure move is non-zero and handle enemy presence at destination
         let mut enemies = <(Entity, &Point)>::query().filter(component::<Enemy>());
         //let mut did_something = false;
-        if delta.x != 0 || delta.y != 0 {
+        // squeezing diagonally past a wall corner is as good as walking into the wall
+        if (delta.x != 0 || delta.y != 0) && map.can_step(player_pos, destination) {
             let mut hit_something = false;
             enemies
                 .iter(ecs)
@@ -83,6 +212,7 @@ pub fn player_input(
                         WantsToAttack {
                             attacker: player_entity,
                             victim: *entity,
+                            ranged: None,
                         },
                     ));
                 });
@@ -115,27 +245,25 @@ pub fn player_input(
     }
 }
 
-fn use_item(n: usize, ecs: &mut SubWorld, commands: &mut CommandBuffer) -> Point {
-    let player_entity = <(Entity, &Player)>::query()
-        .iter(ecs)
-        .find_map(|(entity, _player)| Some(*entity))
-        .unwrap();
-
-    let item_entity = <(Entity, &Item, &Carried)>::query()
-        .iter(ecs)
-        .filter(|(_, _, carried)| carried.0 == player_entity)
-        .enumerate()
-        .filter(|(item_count, (_, _, _))| *item_count == n)
-        .find_map(|(_, (item_entity, _, _))| Some(*item_entity));
-
-    if let Some(item_entity) = item_entity {
-        commands.push((
-            (),
-            ActivateItem {
-                used_by: player_entity,
-                item: item_entity,
-            },
-        ));
+// shuts the first open door next to the player that nothing is standing in
+fn close_door(ecs: &SubWorld, commands: &mut CommandBuffer, map: &mut Map, pos: Point) -> bool {
+    let occupied: Vec<Point> = <&Point>::query().iter(ecs).cloned().collect();
+    let door = map
+        .movement
+        .directions()
+        .iter()
+        .map(|delta| pos + *delta)
+        .filter(|pt| !occupied.contains(pt))
+        .find_map(|pt| {
+            map.try_idx(pt)
+                .filter(|idx| map.tiles[*idx] == TileType::Door { open: true })
+        });
+    match door {
+        Some(idx) => {
+            map.tiles[idx] = TileType::Door { open: false };
+            refresh_views(ecs, commands);
+            true
+        }
+        None => false,
     }
-    Point::zero()
 }
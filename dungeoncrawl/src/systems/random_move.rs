@@ -1,3 +1,4 @@
+use super::chasing::{mobility_of, move_or_attack};
 use crate::prelude::*;
 
 #[system]
@@ -5,49 +6,48 @@ use crate::prelude::*;
 #[read_component(MovingRandomly)]
 #[read_component(Health)]
 #[read_component(Player)]
-pub fn random_move(ecs: &SubWorld, commands: &mut CommandBuffer) {
-    let mut movers = <(Entity, &Point, &MovingRandomly)>::query();
-    let mut positions = <(Entity, &Point, &Health)>::query();
+#[read_component(StatusEffects)]
+#[read_component(Energy)]
+#[read_component(Acted)]
+#[read_component(Sleeping)]
+#[read_component(OpensDoors)]
+#[read_component(Swims)]
+pub fn random_move(
+    ecs: &SubWorld,
+    commands: &mut CommandBuffer,
+    #[resource] rng: &mut RandomNumberGenerator,
+    #[resource] map: &Map,
+) {
+    // confused monsters stumble about just like the ones that always wander
+    let mut movers = <(
+        Entity,
+        &Point,
+        Option<&MovingRandomly>,
+        Option<&StatusEffects>,
+    )>::query()
+    .filter(!component::<Player>() & !component::<Sleeping>());
 
-    movers.iter(ecs).for_each(|(entity, pos, _)| {
-        let mut rng = RandomNumberGenerator::new();
-        let destination = match rng.range(0, 4) {
-            0 => Point::new(-1, 0),
-            1 => Point::new(1, 0),
-            2 => Point::new(0, -1),
-            _ => Point::new(0, 1),
-        } + *pos;
-
-        let mut attacked = false;
-        positions
-            .iter(ecs)
-            .filter(|(_, target_pos, _)| **target_pos == destination)
-            .for_each(|(victim, _, _)| {
-                if ecs
-                    .entry_ref(*victim)
-                    .unwrap()
-                    .get_component::<Player>()
-                    .is_ok()
-                {
-                    commands.push((
-                        (),
-                        WantsToAttack {
-                            attacker: *entity,
-                            victim: *victim,
-                        },
-                    ));
-                }
-                attacked = true;
-            });
+    movers
+        .iter(ecs)
+        .for_each(|(entity, pos, wandering, effects)| {
+            let confused = effects.is_some_and(|effects| effects.has(StatusKind::Confusion));
+            if wandering.is_none() && !confused {
+                return;
+            }
+            if !can_act(ecs, *entity) {
+                return;
+            }
+            let destination = random_direction(rng, map.movement) + *pos;
+            if map
+                .pathing(mobility_of(ecs, *entity))
+                .can_step(*pos, destination)
+            {
+                move_or_attack(ecs, commands, *entity, destination);
+            }
+        })
+}
 
-        if !attacked {
-            commands.push((
-                (),
-                WantsToMove {
-                    entity: *entity,
-                    destination,
-                },
-            ));
-        }
-    })
+pub fn random_direction(rng: &mut RandomNumberGenerator, movement: Movement) -> Point {
+    let directions = movement.directions();
+    directions[rng.range(0, directions.len())]
 }
@@ -0,0 +1,137 @@
+use super::chasing::{mobility_of, overridden};
+use crate::prelude::*;
+
+// once recharged, a monster that can see the player has a one in this many chance to use a special attack
+const SPECIAL_CHANCE: i32 = 2;
+
+#[system]
+#[read_component(Point)]
+#[read_component(Player)]
+#[read_component(Name)]
+#[read_component(FieldOfView)]
+#[read_component(Sleeping)]
+#[read_component(OpensDoors)]
+#[read_component(Swims)]
+#[write_component(Health)]
+#[read_component(Energy)]
+#[read_component(Acted)]
+#[write_component(SpecialAttacks)]
+#[write_component(StatusEffects)]
+pub fn specials(
+    ecs: &mut SubWorld,
+    commands: &mut CommandBuffer,
+    #[resource] map: &Map,
+    #[resource] rng: &mut RandomNumberGenerator,
+    #[resource] log: &mut GameLog,
+    #[resource] templates: &Templates,
+) {
+    let (player, player_pos) = <(Entity, &Point)>::query()
+        .filter(component::<Player>())
+        .iter(ecs)
+        .map(|(entity, pos)| (*entity, *pos))
+        .next()
+        .unwrap();
+
+    let casters: Vec<(Entity, Point, String)> =
+        <(Entity, &Point, &Name, &FieldOfView, &SpecialAttacks)>::query()
+            .filter(!component::<Sleeping>())
+            .iter(ecs)
+            .filter(|(entity, _, _, fov, _)| {
+                fov.visible_tiles.contains(&player_pos)
+                    && can_act(ecs, **entity)
+                    && !overridden(ecs, **entity)
+            })
+            .map(|(entity, pos, name, _, _)| (*entity, *pos, name.0.clone()))
+            .collect();
+
+    for (caster, pos, name) in casters {
+        let free_tiles = free_tiles_around(ecs, map, caster, pos);
+        let attack = {
+            let mut entry = ecs.entry_mut(caster).unwrap();
+            let specials = entry.get_component_mut::<SpecialAttacks>().unwrap();
+            if specials.recharge > 0 {
+                specials.recharge -= 1;
+                continue;
+            }
+            let usable: Vec<SpecialAttack> = specials
+                .attacks
+                .iter()
+                .filter(|attack| match attack {
+                    SpecialAttack::Slam { radius, .. } => {
+                        DistanceAlg::Pythagoras.distance2d(pos, player_pos) <= *radius as f32
+                    }
+                    SpecialAttack::Bolt { range, .. } => in_range(map, pos, player_pos, *range),
+                    SpecialAttack::Summon { .. } => !free_tiles.is_empty(),
+                })
+                .cloned()
+                .collect();
+            if usable.is_empty() || rng.range(0, SPECIAL_CHANCE) != 0 {
+                continue;
+            }
+            specials.recharge = specials.cooldown;
+            rng.random_slice_entry(&usable).cloned().unwrap()
+        };
+
+        log.push(GameEvent::UsedSpecial {
+            attacker: name.clone(),
+            attack: attack.label().to_string(),
+        });
+        match attack {
+            SpecialAttack::Slam { damage, .. } => hurt(ecs, player, &name, damage, log),
+            SpecialAttack::Bolt { damage, status, .. } => {
+                hurt(ecs, player, &name, damage, log);
+                if let Some((kind, turns)) = status {
+                    apply_status(ecs, commands, player, &[StatusEffect { kind, turns }], log);
+                }
+            }
+            SpecialAttack::Summon { template, count } => {
+                free_tiles.iter().take(count).for_each(|pt| {
+                    templates.summon(commands, rng, &template, pt);
+                    log.push(GameEvent::Summoned {
+                        minion: template.clone(),
+                    });
+                });
+            }
+        }
+
+        // the special attack is the monster's whole turn, so the other AI systems pass it by
+        commands.add_component(caster, Acted);
+    }
+}
+
+// special attacks go straight through armor
+fn hurt(ecs: &mut SubWorld, victim: Entity, attacker: &str, damage: i32, log: &mut GameLog) {
+    if let Ok(mut entry) = ecs.entry_mut(victim) {
+        let victim_name = entry
+            .get_component::<Name>()
+            .map(|name| name.0.clone())
+            .unwrap_or_default();
+        if let Ok(health) = entry.get_component_mut::<Health>() {
+            health.current -= damage;
+            log.push(GameEvent::Attack {
+                attacker: attacker.to_string(),
+                victim: victim_name,
+                damage,
+            });
+        }
+    }
+}
+
+// minions appear where the caster could step, never in lava, a chasm or a closed doorway
+fn free_tiles_around(ecs: &SubWorld, map: &Map, caster: Entity, pos: Point) -> Vec<Point> {
+    let pathing = map.pathing(Mobility {
+        opens_doors: false,
+        ..mobility_of(ecs, caster)
+    });
+    let occupied: Vec<Point> = <&Point>::query()
+        .filter(component::<Health>())
+        .iter(ecs)
+        .cloned()
+        .collect();
+    map.movement
+        .directions()
+        .iter()
+        .map(|delta| pos + *delta)
+        .filter(|pt| pathing.can_step(pos, *pt) && !occupied.contains(pt))
+        .collect()
+}
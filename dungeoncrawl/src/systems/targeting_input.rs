@@ -0,0 +1,156 @@
+use crate::prelude::*;
+
+#[system]
+#[read_component(Point)]
+#[read_component(Player)]
+#[read_component(Enemy)]
+#[read_component(FieldOfView)]
+#[read_component(Ranged)]
+#[read_component(Provides)]
+#[read_component(Health)]
+#[allow(clippy::too_many_arguments)]
+pub fn targeting_input(
+    ecs: &SubWorld,
+    commands: &mut CommandBuffer,
+    #[resource] key: &Option<VirtualKeyCode>,
+    #[resource] mouse_pos: &Point,
+    #[resource] click: &MouseClick,
+    #[resource] camera: &Camera,
+    #[resource] map: &Map,
+    #[resource] turn_state: &mut TurnState,
+    #[resource] targeting: &mut Targeting,
+    #[resource] log: &mut GameLog,
+) {
+    let player = <Entity>::query()
+        .filter(component::<Player>())
+        .iter(ecs)
+        .cloned()
+        .next()
+        .unwrap();
+    let weapon = match targeting.weapon {
+        Some(weapon) => weapon,
+        None => {
+            *turn_state = TurnState::AwaitingInput;
+            return;
+        }
+    };
+    let range = weapon_range(ecs, weapon);
+    let targets = ranged_targets(ecs, player, range, map);
+    let current = targeting
+        .target
+        .and_then(|target| targets.iter().position(|entity| *entity == target));
+    let mut fire = false;
+
+    // clicking an enemy or an open tile aims at it, clicking it again lets fly
+    if click.0 {
+        let map_pos = *mouse_pos + Point::new(camera.left_x, camera.top_y);
+        let clicked = targets
+            .iter()
+            .position(|entity| entity_pos(ecs, *entity) == Some(map_pos));
+        if let Some(clicked) = clicked {
+            fire = current == Some(clicked);
+            targeting.target = Some(targets[clicked]);
+            targeting.target_tile = None;
+        } else if tile_in_reach(ecs, player, map_pos, range, map) {
+            fire = targeting.target_tile == Some(map_pos);
+            targeting.target = None;
+            targeting.target_tile = Some(map_pos);
+        }
+    }
+
+    if let Some(key) = key {
+        match key {
+            VirtualKeyCode::Escape => {
+                *targeting = Targeting::default();
+                *turn_state = TurnState::AwaitingInput;
+                return;
+            }
+            VirtualKeyCode::Tab | VirtualKeyCode::Right | VirtualKeyCode::Down
+                if !targets.is_empty() =>
+            {
+                let next = current.map(|idx| (idx + 1) % targets.len()).unwrap_or(0);
+                targeting.target = Some(targets[next]);
+                targeting.target_tile = None;
+            }
+            VirtualKeyCode::Left | VirtualKeyCode::Up if !targets.is_empty() => {
+                let prev = current
+                    .map(|idx| (idx + targets.len() - 1) % targets.len())
+                    .unwrap_or(0);
+                targeting.target = Some(targets[prev]);
+                targeting.target_tile = None;
+            }
+            VirtualKeyCode::Return | VirtualKeyCode::F | VirtualKeyCode::T => fire = true,
+            _ => {}
+        }
+    }
+
+    if !fire {
+        return;
+    }
+    // an enemy is aimed at where it stands, a picked tile at whoever is on it when the shot lands
+    let aim = match targeting.target.filter(|target| targets.contains(target)) {
+        Some(victim) => entity_pos(ecs, victim).map(|tile| (tile, Some(victim))),
+        None => targeting
+            .target_tile
+            .filter(|tile| tile_in_reach(ecs, player, *tile, range, map))
+            .map(|tile| (tile, standing_on(ecs, tile))),
+    };
+    let (tile, victim) = match aim {
+        Some(aim) => aim,
+        None => {
+            log.push(GameEvent::NoTarget);
+            return;
+        }
+    };
+
+    // scrolls are read at the target tile, weapons attack whoever is there
+    let is_weapon = ecs
+        .entry_ref(weapon)
+        .map(|entry| entry.get_component::<Provides>().is_err())
+        .unwrap_or(true);
+    if !is_weapon {
+        commands.push((
+            (),
+            ActivateItem {
+                used_by: player,
+                item: weapon,
+                target: Some(tile),
+            },
+        ));
+    } else if let Some(victim) = victim {
+        commands.push((
+            (),
+            WantsToAttack {
+                attacker: player,
+                victim,
+                ranged: Some(weapon),
+            },
+        ));
+    } else {
+        commands.push((
+            (),
+            WantsToShootTile {
+                attacker: player,
+                weapon,
+                tile,
+            },
+        ));
+    }
+    *targeting = Targeting::default();
+    *turn_state = TurnState::PlayerTurn;
+}
+
+fn entity_pos(ecs: &SubWorld, entity: Entity) -> Option<Point> {
+    ecs.entry_ref(entity)
+        .ok()
+        .and_then(|entry| entry.get_component::<Point>().ok().cloned())
+}
+
+// anyone but the shooter who could take the hit
+fn standing_on(ecs: &SubWorld, tile: Point) -> Option<Entity> {
+    <(Entity, &Point)>::query()
+        .filter(component::<Health>() & !component::<Player>())
+        .iter(ecs)
+        .find(|(_, pos)| **pos == tile)
+        .map(|(entity, _)| *entity)
+}
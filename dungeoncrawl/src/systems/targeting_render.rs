@@ -0,0 +1,45 @@
+use crate::prelude::*;
+
+#[system]
+#[read_component(Point)]
+#[read_component(Name)]
+#[read_component(Health)]
+pub fn targeting_render(
+    ecs: &SubWorld,
+    #[resource] targeting: &Targeting,
+    #[resource] camera: &Camera,
+) {
+    let mut draw_batch = DrawBatch::new();
+    draw_batch.target(2);
+
+    let weapon_name = targeting
+        .weapon
+        .and_then(|weapon| ecs.entry_ref(weapon).ok())
+        .and_then(|entry| entry.get_component::<Name>().ok().cloned())
+        .map(|name| name.0)
+        .unwrap_or_default();
+    draw_batch.print_color_centered(
+        2,
+        format!(
+            "Aiming the {}: Tab or click to pick a target, Enter or click again to fire, Escape to cancel.",
+            weapon_name
+        ),
+        ColorPair::new(ORANGE, BLACK),
+    );
+
+    let aimed_at = targeting
+        .target
+        .and_then(|target| ecs.entry_ref(target).ok())
+        .and_then(|entry| entry.get_component::<Point>().ok().cloned())
+        .or(targeting.target_tile);
+    if let Some(pos) = aimed_at {
+        // the text layer has four cells for every map tile
+        let screen_pos = (pos - Point::new(camera.left_x, camera.top_y)) * 4;
+        draw_batch.draw_hollow_box(
+            Rect::with_size(screen_pos.x - 1, screen_pos.y - 1, 5, 5),
+            ColorPair::new(RED, BLACK),
+        );
+    }
+
+    draw_batch.submit(10200).expect("Batch error");
+}
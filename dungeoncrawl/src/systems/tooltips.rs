@@ -1,3 +1,4 @@
+use super::chasing::is_afraid;
 use crate::prelude::*;
 
 #[system]
@@ -6,8 +7,17 @@ use crate::prelude::*;
 #[read_component(Health)]
 #[read_component(FieldOfView)]
 #[read_component(Player)]
+#[read_component(StatusEffects)]
+#[read_component(Enemy)]
+#[read_component(FleesWhenHurt)]
+#[read_component(Sleeping)]
+#[read_component(LastSeenPlayer)]
+#[read_component(Patrolling)]
+#[read_component(Guarding)]
+#[read_component(MovingRandomly)]
+#[read_component(Hidden)]
 pub fn tooltips(ecs: &SubWorld, #[resource] mouse_pos: &Point, #[resource] camera: &Camera) {
-    let mut positions = <(Entity, &Point, &Name)>::query();
+    let mut positions = <(Entity, &Point, &Name)>::query().filter(!component::<Hidden>());
     let mut fov = <&FieldOfView>::query().filter(component::<Player>());
 
     let offset = Point::new(camera.left_x, camera.top_y);
@@ -15,18 +25,37 @@ pub fn tooltips(ecs: &SubWorld, #[resource] mouse_pos: &Point, #[resource] camer
     let mut draw_batch = DrawBatch::new();
     draw_batch.target(2);
     let player_fov = fov.iter(ecs).nth(0).unwrap();
+    let player_pos = <&Point>::query()
+        .filter(component::<Player>())
+        .iter(ecs)
+        .next()
+        .cloned()
+        .unwrap_or_else(Point::zero);
 
     positions
         .iter(ecs)
         .filter(|(_, pos, _)| **pos == map_pos && player_fov.visible_tiles.contains(&pos))
         .for_each(|(entity, _, name)| {
             let screen_pos = *mouse_pos * 4;
-            let display =
-                if let Ok(health) = ecs.entry_ref(*entity).unwrap().get_component::<Health>() {
-                    format!("{} : {} hp", &name.0, health.current)
-                } else {
-                    name.0.clone()
-                };
+            let entry = ecs.entry_ref(*entity).unwrap();
+            let mut display = if let Ok(health) = entry.get_component::<Health>() {
+                format!("{} : {} hp", &name.0, health.current)
+            } else {
+                name.0.clone()
+            };
+            if let Some(state) = monster_state(ecs, *entity, player_pos) {
+                display = format!("{}, {}", display, state);
+            }
+            if let Ok(effects) = entry.get_component::<StatusEffects>() {
+                if !effects.0.is_empty() {
+                    let adjectives: Vec<&str> = effects
+                        .0
+                        .iter()
+                        .map(|effect| effect.kind.adjective())
+                        .collect();
+                    display = format!("{} ({})", display, adjectives.join(", "));
+                }
+            }
             draw_batch.print(screen_pos, &display);
         });
     /*
@@ -37,3 +66,41 @@ pub fn tooltips(ecs: &SubWorld, #[resource] mouse_pos: &Point, #[resource] camer
     */
     draw_batch.submit(10100).expect("Batch error");
 }
+
+// what a monster is up to, as far as the player can tell
+fn monster_state(ecs: &SubWorld, entity: Entity, player_pos: Point) -> Option<&'static str> {
+    let entry = ecs.entry_ref(entity).ok()?;
+    entry.get_component::<Enemy>().ok()?;
+    let sees_player = entry
+        .get_component::<FieldOfView>()
+        .is_ok_and(|fov| fov.visible_tiles.contains(&player_pos));
+    let state = if entry.get_component::<Sleeping>().is_ok() {
+        "asleep"
+    } else if is_afraid(ecs, entity) {
+        "fleeing"
+    } else if let Ok(guarding) = entry.get_component::<Guarding>() {
+        let intruder = sees_player
+            && DistanceAlg::Pythagoras.distance2d(guarding.post, player_pos)
+                <= guarding.radius as f32;
+        if intruder {
+            "hunting"
+        } else {
+            "guarding"
+        }
+    } else if entry.get_component::<MovingRandomly>().is_ok() {
+        "wandering"
+    } else if sees_player {
+        "hunting"
+    } else if let Ok(last_seen) = entry.get_component::<LastSeenPlayer>() {
+        if last_seen.arrived {
+            "searching"
+        } else {
+            "hunting"
+        }
+    } else if entry.get_component::<Patrolling>().is_ok() {
+        "patrolling"
+    } else {
+        "idle"
+    };
+    Some(state)
+}
@@ -0,0 +1,163 @@
+use super::chasing::SEARCH_TURNS;
+use super::use_items::teleport_destination;
+use crate::prelude::*;
+
+// searching turns up anything hidden this close to the player
+pub const SEARCH_RADIUS: f32 = 2.5;
+// without searching, hidden things right next to the player are noticed one turn in this many
+const PERCEPTION_RADIUS: f32 = 1.5;
+const PERCEPTION_CHANCE: i32 = 4;
+// how far an alarm trap's ringing carries
+const ALARM_RADIUS: f32 = 20.0;
+
+#[system]
+#[read_component(Point)]
+#[read_component(Player)]
+#[read_component(Hidden)]
+#[read_component(Name)]
+#[read_component(SecretDoor)]
+pub fn perception(
+    ecs: &SubWorld,
+    commands: &mut CommandBuffer,
+    #[resource] map: &mut Map,
+    #[resource] rng: &mut RandomNumberGenerator,
+    #[resource] log: &mut GameLog,
+) {
+    let player_pos = match <&Point>::query()
+        .filter(component::<Player>())
+        .iter(ecs)
+        .next()
+    {
+        Some(pos) => *pos,
+        None => return,
+    };
+    hidden_near(ecs, player_pos, PERCEPTION_RADIUS)
+        .into_iter()
+        .filter(|_| rng.range(0, PERCEPTION_CHANCE) == 0)
+        .for_each(|entity| reveal(ecs, commands, map, log, entity));
+}
+
+// uncovers everything hidden around `pos`, returning whether anything turned up
+pub fn search(
+    ecs: &SubWorld,
+    commands: &mut CommandBuffer,
+    map: &mut Map,
+    log: &mut GameLog,
+    pos: Point,
+) -> bool {
+    let found = hidden_near(ecs, pos, SEARCH_RADIUS);
+    found
+        .iter()
+        .for_each(|entity| reveal(ecs, commands, map, log, *entity));
+    !found.is_empty()
+}
+
+fn hidden_near(ecs: &SubWorld, pos: Point, radius: f32) -> Vec<Entity> {
+    <(Entity, &Point)>::query()
+        .filter(component::<Hidden>())
+        .iter(ecs)
+        .filter(|(_, pt)| DistanceAlg::Pythagoras.distance2d(pos, **pt) <= radius)
+        .map(|(entity, _)| *entity)
+        .collect()
+}
+
+// a found secret door becomes a real one; anything else just stops being hidden
+fn reveal(
+    ecs: &SubWorld,
+    commands: &mut CommandBuffer,
+    map: &mut Map,
+    log: &mut GameLog,
+    entity: Entity,
+) {
+    if let Ok(entry) = ecs.entry_ref(entity) {
+        if entry.get_component::<SecretDoor>().is_ok() {
+            let idx = map.point2d_to_index(*entry.get_component::<Point>().unwrap());
+            map.tiles[idx] = TileType::Door { open: false };
+            commands.remove(entity);
+            log.push(GameEvent::FoundSecretDoor);
+        } else {
+            commands.remove_component::<Hidden>(entity);
+            if let Ok(name) = entry.get_component::<Name>() {
+                log.push(GameEvent::FoundTrap {
+                    trap: name.0.clone(),
+                });
+            }
+        }
+    }
+}
+
+// called by movement when the player steps onto `pos`; monsters know where their traps are
+#[allow(clippy::too_many_arguments)]
+pub fn spring_trap(
+    ecs: &mut SubWorld,
+    commands: &mut CommandBuffer,
+    map: &Map,
+    camera: &mut Camera,
+    rng: &mut RandomNumberGenerator,
+    log: &mut GameLog,
+    victim: Entity,
+    pos: Point,
+) {
+    let sprung = <(Entity, &Point, &Trap, &Name)>::query()
+        .iter(ecs)
+        .find(|(_, trap_pos, _, _)| **trap_pos == pos)
+        .map(|(entity, _, trap, name)| (*entity, *trap, name.0.clone()));
+    let (trap_entity, trap, trap_name) = match sprung {
+        Some(sprung) => sprung,
+        None => return,
+    };
+    // a sprung trap gives itself away
+    commands.remove_component::<Hidden>(trap_entity);
+    log.push(GameEvent::SprungTrap {
+        trap: trap_name.clone(),
+    });
+
+    match trap {
+        Trap::Dart(damage) => {
+            if let Ok(mut entry) = ecs.entry_mut(victim) {
+                let victim_name = entry.get_component::<Name>().unwrap().0.clone();
+                if let Ok(health) = entry.get_component_mut::<Health>() {
+                    health.current -= damage;
+                    log.push(GameEvent::Attack {
+                        attacker: trap_name,
+                        victim: victim_name,
+                        damage,
+                    });
+                }
+            }
+        }
+        Trap::Teleport => {
+            if let Some(destination) = teleport_destination(ecs, map, rng) {
+                // replaces the step movement just made
+                commands.add_component(victim, destination);
+                camera.on_player_move(destination);
+                log.push(GameEvent::Teleported);
+            }
+        }
+        Trap::Alarm => {
+            let in_earshot =
+                |pt: &Point| DistanceAlg::Pythagoras.distance2d(pos, *pt) <= ALARM_RADIUS;
+            <(Entity, &Point)>::query()
+                .filter(component::<Sleeping>())
+                .iter(ecs)
+                .filter(|(_, pt)| in_earshot(pt))
+                .for_each(|(entity, _)| commands.remove_component::<Sleeping>(*entity));
+            // hunters come to see what set it off
+            <(Entity, &Point)>::query()
+                .filter(component::<ChasingPlayer>())
+                .iter(ecs)
+                .filter(|(_, pt)| in_earshot(pt))
+                .for_each(|(entity, _)| {
+                    commands.add_component(
+                        *entity,
+                        LastSeenPlayer {
+                            pos,
+                            arrived: false,
+                            search_turns: SEARCH_TURNS,
+                        },
+                    )
+                });
+            log.push(GameEvent::AlarmSounded);
+        }
+    }
+}
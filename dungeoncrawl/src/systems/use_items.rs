@@ -1,34 +1,211 @@
+use super::combat::kill;
 use crate::prelude::*;
 
 #[system]
 #[read_component(ActivateItem)]
-#[read_component(ProvidesHealing)]
+#[read_component(Provides)]
 #[write_component(Health)]
-#[read_component(ProvidesDungeonMap)]
-pub fn use_items(ecs: &mut SubWorld, commands: &mut CommandBuffer, #[resource] map: &mut Map) {
-    let mut healing_to_apply = Vec::<(Entity, i32)>::new();
+#[read_component(Name)]
+#[write_component(StatusEffects)]
+#[read_component(Point)]
+#[read_component(Player)]
+#[read_component(Enemy)]
+#[read_component(FieldOfView)]
+#[write_component(Experience)]
+#[write_component(Damage)]
+#[read_component(Boss)]
+#[read_component(ExperienceValue)]
+pub fn use_items(
+    ecs: &mut SubWorld,
+    commands: &mut CommandBuffer,
+    #[resource] map: &mut Map,
+    #[resource] rng: &mut RandomNumberGenerator,
+    #[resource] log: &mut GameLog,
+    #[resource] summary: &mut RunSummary,
+) {
+    let mut effects_to_apply = Vec::<(ActivateItem, String, Effect)>::new();
 
     <(Entity, &ActivateItem)>::query()
         .iter(ecs)
         .for_each(|(entity, activate)| {
             let item = ecs.entry_ref(activate.item);
             if let Ok(item) = item {
-                if let Ok(healing) = item.get_component::<ProvidesHealing>() {
-                    healing_to_apply.push((activate.used_by, healing.amount));
-                }
-                if let Ok(_mapper) = item.get_component::<ProvidesDungeonMap>() {
-                    map.revealed_tiles.iter_mut().for_each(|t| *t = true);
+                let name = item
+                    .get_component::<Name>()
+                    .map(|name| name.0.clone())
+                    .unwrap_or_default();
+                log.push(GameEvent::UsedItem { item: name.clone() });
+                if let Ok(provides) = item.get_component::<Provides>() {
+                    provides.0.iter().for_each(|effect| {
+                        effects_to_apply.push((*activate, name.clone(), *effect));
+                    });
                 }
             }
             commands.remove(activate.item);
             commands.remove(*entity);
         });
 
-    for heal in healing_to_apply.iter() {
-        if let Ok(mut target) = ecs.entry_mut(heal.0) {
-            if let Ok(health) = target.get_component_mut::<Health>() {
-                health.current = i32::min(health.max, health.current + heal.1);
+    for (activate, item_name, effect) in effects_to_apply.iter() {
+        let user = activate.used_by;
+        match effect {
+            Effect::Healing(amount) => heal(ecs, user, *amount, log),
+            Effect::MagicMap => {
+                map.revealed_tiles.iter_mut().for_each(|t| *t = true);
+                log.push(GameEvent::RevealedMap);
+            }
+            Effect::Status(kind, turns) => {
+                let effect = StatusEffect {
+                    kind: *kind,
+                    turns: *turns,
+                };
+                apply_status(ecs, commands, user, &[effect], log);
+            }
+            Effect::Teleport => teleport(ecs, commands, user, map, rng, log),
+            Effect::Fireball { damage, radius } => {
+                if let Some(center) = activate.target {
+                    explode(
+                        ecs, commands, map, user, item_name, center, *damage, *radius, log, summary,
+                    );
+                }
+            }
+            Effect::Fear(turns) => {
+                let effect = StatusEffect {
+                    kind: StatusKind::Fear,
+                    turns: *turns,
+                };
+                visible_enemies(ecs, user).iter().for_each(|enemy| {
+                    apply_status(ecs, commands, *enemy, &[effect], log);
+                });
+            }
+            Effect::RevealMonsters(turns) => {
+                let effect = StatusEffect {
+                    kind: StatusKind::Telepathy,
+                    turns: *turns,
+                };
+                apply_status(ecs, commands, user, &[effect], log);
             }
         }
     }
 }
+
+fn heal(ecs: &mut SubWorld, target: Entity, amount: i32, log: &mut GameLog) {
+    if let Ok(mut target) = ecs.entry_mut(target) {
+        if let Ok(health) = target.get_component_mut::<Health>() {
+            let healed = i32::min(health.max, health.current + amount);
+            log.push(GameEvent::Healed {
+                amount: healed - health.current,
+            });
+            health.current = healed;
+        }
+    }
+}
+
+// lands on a random empty floor tile; movement takes care of the camera and field of view
+fn teleport(
+    ecs: &SubWorld,
+    commands: &mut CommandBuffer,
+    user: Entity,
+    map: &Map,
+    rng: &mut RandomNumberGenerator,
+    log: &mut GameLog,
+) {
+    if let Some(destination) = teleport_destination(ecs, map, rng) {
+        commands.push((
+            (),
+            WantsToMove {
+                entity: user,
+                destination,
+            },
+        ));
+        log.push(GameEvent::Teleported);
+    }
+}
+
+// a random open floor tile with nobody standing on it
+pub fn teleport_destination(
+    ecs: &SubWorld,
+    map: &Map,
+    rng: &mut RandomNumberGenerator,
+) -> Option<Point> {
+    let occupied: Vec<Point> = <&Point>::query()
+        .filter(component::<Health>())
+        .iter(ecs)
+        .cloned()
+        .collect();
+    let destinations: Vec<Point> = map
+        .tiles
+        .iter()
+        .enumerate()
+        .filter(|(_, tile)| **tile == TileType::Floor)
+        .map(|(idx, _)| map.index_to_point2d(idx))
+        .filter(|pt| !occupied.contains(pt))
+        .collect();
+    rng.random_slice_entry(&destinations).cloned()
+}
+
+#[allow(clippy::too_many_arguments)]
+fn explode(
+    ecs: &mut SubWorld,
+    commands: &mut CommandBuffer,
+    map: &Map,
+    user: Entity,
+    item_name: &str,
+    center: Point,
+    damage: i32,
+    radius: i32,
+    log: &mut GameLog,
+    summary: &mut RunSummary,
+) {
+    // the blast doesn't care whose side anyone is on, the reader included, but walls and
+    // closed doors keep it out
+    let caught: Vec<Entity> = <(Entity, &Point, &Health)>::query()
+        .iter(ecs)
+        .filter(|(_, pos, health)| {
+            health.current > 0
+                && DistanceAlg::Pythagoras.distance2d(center, **pos) <= radius as f32
+                && in_line_of_sight(map, center, **pos)
+        })
+        .map(|(entity, _, _)| *entity)
+        .collect();
+
+    let mut killed = Vec::new();
+    caught.iter().for_each(|victim| {
+        let mut entry = ecs.entry_mut(*victim).unwrap();
+        let is_player = entry.get_component::<Player>().is_ok();
+        let victim_name = entry
+            .get_component::<Name>()
+            .map(|name| name.0.clone())
+            .unwrap_or_default();
+        if let Ok(health) = entry.get_component_mut::<Health>() {
+            health.current -= damage;
+            log.push(GameEvent::Attack {
+                attacker: item_name.to_string(),
+                victim: victim_name,
+                damage,
+            });
+            if health.current < 1 && !is_player {
+                killed.push(*victim);
+            }
+        }
+    });
+    killed.iter().for_each(|victim| {
+        kill(ecs, commands, log, summary, *victim, Some(user), item_name);
+    });
+}
+
+fn visible_enemies(ecs: &SubWorld, viewer: Entity) -> Vec<Entity> {
+    let fov = match ecs
+        .entry_ref(viewer)
+        .ok()
+        .and_then(|entry| entry.get_component::<FieldOfView>().ok().cloned())
+    {
+        Some(fov) => fov,
+        None => return Vec::new(),
+    };
+    <(Entity, &Point)>::query()
+        .filter(component::<Enemy>())
+        .iter(ecs)
+        .filter(|(_, pos)| fov.visible_tiles.contains(pos))
+        .map(|(entity, _)| *entity)
+        .collect()
+}
@@ -0,0 +1,43 @@
+use crate::prelude::*;
+
+// sleepers always wake when the player comes this close
+const HEARING_RANGE: f32 = 2.5;
+// otherwise a sleeper that can see the player has a one in this many chance to notice them
+const NOTICE_CHANCE: i32 = 3;
+
+#[system]
+#[read_component(Point)]
+#[read_component(Sleeping)]
+#[read_component(FieldOfView)]
+#[read_component(Player)]
+#[read_component(Name)]
+#[read_component(Energy)]
+#[read_component(Acted)]
+pub fn wake_up(
+    ecs: &SubWorld,
+    commands: &mut CommandBuffer,
+    #[resource] rng: &mut RandomNumberGenerator,
+    #[resource] log: &mut GameLog,
+) {
+    let player_pos = *<&Point>::query()
+        .filter(component::<Player>())
+        .iter(ecs)
+        .next()
+        .unwrap();
+
+    <(Entity, &Point, &FieldOfView, &Name)>::query()
+        .filter(component::<Sleeping>())
+        .iter(ecs)
+        .filter(|(entity, _, _, _)| can_act(ecs, **entity))
+        .for_each(|(entity, pos, fov, name)| {
+            let heard = DistanceAlg::Pythagoras.distance2d(*pos, player_pos) <= HEARING_RANGE;
+            let noticed =
+                fov.visible_tiles.contains(&player_pos) && rng.range(0, NOTICE_CHANCE) == 0;
+            if heard || noticed {
+                commands.remove_component::<Sleeping>(*entity);
+                log.push(GameEvent::WokeUp {
+                    monster: name.0.clone(),
+                });
+            }
+        });
+}
@@ -0,0 +1,78 @@
+use crate::prelude::*;
+
+// the weapon being fired or the item being thrown, and the enemy or tile it's aimed at
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Targeting {
+    pub weapon: Option<Entity>,
+    pub target: Option<Entity>,
+    // a tile picked with the mouse; only set while no enemy is picked
+    pub target_tile: Option<Point>,
+}
+
+// projectiles fly in a straight line and stop at the first opaque tile
+pub fn in_line_of_sight(map: &Map, from: Point, to: Point) -> bool {
+    line2d_bresenham(from, to)
+        .iter()
+        .filter(|pt| **pt != from && **pt != to)
+        .all(|pt| map.in_bounds(*pt) && !map.is_opaque(map.point2d_to_index(*pt)))
+}
+
+pub fn in_range(map: &Map, from: Point, to: Point, range: i32) -> bool {
+    DistanceAlg::Pythagoras.distance2d(from, to) <= range as f32 && in_line_of_sight(map, from, to)
+}
+
+pub fn weapon_range(ecs: &SubWorld, weapon: Entity) -> i32 {
+    ecs.entry_ref(weapon)
+        .ok()
+        .and_then(|entry| entry.get_component::<Ranged>().ok().cloned())
+        .map(|ranged| ranged.range)
+        .unwrap_or(1)
+}
+
+// visible enemies the shooter could hit, nearest first
+pub fn ranged_targets(ecs: &SubWorld, shooter: Entity, range: i32, map: &Map) -> Vec<Entity> {
+    let shooter_entry = ecs.entry_ref(shooter).unwrap();
+    let shooter_pos = *shooter_entry.get_component::<Point>().unwrap();
+    let fov = shooter_entry.get_component::<FieldOfView>().unwrap();
+
+    let mut targets: Vec<(Entity, f32)> = <(Entity, &Point)>::query()
+        .filter(component::<Enemy>())
+        .iter(ecs)
+        .filter(|(_, pos)| fov.visible_tiles.contains(pos))
+        .filter(|(_, pos)| in_range(map, shooter_pos, **pos, range))
+        .map(|(entity, pos)| {
+            (
+                *entity,
+                DistanceAlg::Pythagoras.distance2d(shooter_pos, *pos),
+            )
+        })
+        .collect();
+    targets.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+    targets.into_iter().map(|(entity, _)| entity).collect()
+}
+
+// aims at the nearest enemy; with none in range the player can still pick a tile with the mouse
+pub fn start_targeting(
+    ecs: &SubWorld,
+    player: Entity,
+    weapon: Entity,
+    map: &Map,
+    targeting: &mut Targeting,
+    turn_state: &mut TurnState,
+) {
+    let range = weapon_range(ecs, weapon);
+    *targeting = Targeting {
+        weapon: Some(weapon),
+        target: ranged_targets(ecs, player, range, map).first().cloned(),
+        target_tile: None,
+    };
+    *turn_state = TurnState::Targeting;
+}
+
+// a tile the shooter can see with nothing in the way and within range
+pub fn tile_in_reach(ecs: &SubWorld, shooter: Entity, tile: Point, range: i32, map: &Map) -> bool {
+    let shooter_entry = ecs.entry_ref(shooter).unwrap();
+    let shooter_pos = *shooter_entry.get_component::<Point>().unwrap();
+    let fov = shooter_entry.get_component::<FieldOfView>().unwrap();
+    fov.visible_tiles.contains(&tile) && in_range(map, shooter_pos, tile, range)
+}
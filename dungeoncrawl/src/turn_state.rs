@@ -1,9 +1,29 @@
-#[derive(Clone, Copy, Debug, PartialEq)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub enum TurnState {
+    MainMenu,
     AwaitingInput,
     PlayerTurn,
     MonsterTurn,
     GameOver,
     Victory,
     NextLevel,
+    PreviousLevel,
+    ViewingLog,
+    Inventory,
+    Targeting,
+}
+
+impl TurnState {
+    // states whose schedules read the key resource; these are what replays record
+    pub fn accepts_input(&self) -> bool {
+        matches!(
+            self,
+            TurnState::AwaitingInput
+                | TurnState::ViewingLog
+                | TurnState::Inventory
+                | TurnState::Targeting
+        )
+    }
 }
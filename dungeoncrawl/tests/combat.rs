@@ -0,0 +1,86 @@
+use dungeoncrawl::prelude::*;
+
+#[test]
+fn a_monster_only_dies_once() {
+    let mut headless = Headless::new(Seed(5), Movement::FourWay);
+    let ecs = &mut headless.game.ecs;
+    let player = *<Entity>::query()
+        .filter(component::<Player>())
+        .iter(ecs)
+        .next()
+        .unwrap();
+    let (monster, value) = <(Entity, &ExperienceValue)>::query()
+        .filter(component::<Enemy>())
+        .iter(ecs)
+        .map(|(entity, value)| (*entity, value.0))
+        .next()
+        .unwrap();
+    ecs.entry(monster)
+        .unwrap()
+        .get_component_mut::<Health>()
+        .unwrap()
+        .current = 1;
+    for _ in 0..2 {
+        ecs.push((
+            (),
+            WantsToAttack {
+                attacker: player,
+                victim: monster,
+                ranged: None,
+            },
+        ));
+    }
+    let xp_before = <&Experience>::query().iter(ecs).next().unwrap().current;
+
+    headless.game.resources.insert(TurnState::PlayerTurn);
+    headless.step(None, Point::zero(), false);
+
+    let kills = headless
+        .game
+        .resources
+        .get::<GameLog>()
+        .unwrap()
+        .entries
+        .iter()
+        .filter(|event| matches!(event, GameEvent::Killed { .. }))
+        .count();
+    assert_eq!(kills, 1);
+    let experience = *<&Experience>::query()
+        .iter(&headless.game.ecs)
+        .next()
+        .unwrap();
+    assert!(experience.current == xp_before + value || experience.level > 1);
+}
+
+#[test]
+fn poison_kills_count_for_the_player() {
+    let mut headless = Headless::new(Seed(5), Movement::FourWay);
+    let ecs = &mut headless.game.ecs;
+    let (monster, value) = <(Entity, &ExperienceValue)>::query()
+        .filter(component::<Enemy>())
+        .iter(ecs)
+        .map(|(entity, value)| (*entity, value.0))
+        .next()
+        .unwrap();
+    let mut entry = ecs.entry(monster).unwrap();
+    entry.get_component_mut::<Health>().unwrap().current = 1;
+    entry.get_component_mut::<StatusEffects>().unwrap().0 = vec![StatusEffect {
+        kind: StatusKind::Poison,
+        turns: 5,
+    }];
+
+    headless.press(VirtualKeyCode::Space, Point::zero());
+
+    let log = headless.game.resources.get::<GameLog>().unwrap();
+    let kills: Vec<&GameEvent> = log
+        .entries
+        .iter()
+        .filter(|event| matches!(event, GameEvent::Killed { .. }))
+        .collect();
+    assert_eq!(kills.len(), 1);
+    let experience = *<&Experience>::query()
+        .iter(&headless.game.ecs)
+        .next()
+        .unwrap();
+    assert!(experience.current == value || experience.level > 1);
+}
@@ -0,0 +1,38 @@
+// shared by the integration tests; each test file only uses part of it
+#![allow(dead_code)]
+
+use dungeoncrawl::prelude::*;
+
+pub const SCRIPT: [VirtualKeyCode; 12] = [
+    VirtualKeyCode::Right,
+    VirtualKeyCode::Right,
+    VirtualKeyCode::Down,
+    VirtualKeyCode::Down,
+    VirtualKeyCode::Left,
+    VirtualKeyCode::Up,
+    VirtualKeyCode::S,
+    VirtualKeyCode::G,
+    VirtualKeyCode::Right,
+    VirtualKeyCode::Up,
+    VirtualKeyCode::Space,
+    VirtualKeyCode::Key1,
+];
+
+// what a run ended with, as far as a player would notice
+pub fn end_state(headless: &Headless) -> (Point, i32, u32, TurnState, Vec<String>) {
+    let ecs = &headless.game.ecs;
+    let (pos, health) = <(&Point, &Health)>::query()
+        .filter(component::<Player>())
+        .iter(ecs)
+        .map(|(pos, health)| (*pos, health.current))
+        .next()
+        .unwrap();
+    let log = headless.game.resources.get::<GameLog>().unwrap();
+    (
+        pos,
+        health,
+        headless.map_level(),
+        headless.game.turn_state(),
+        log.entries.iter().map(|event| event.text()).collect(),
+    )
+}
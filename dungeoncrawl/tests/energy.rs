@@ -0,0 +1,28 @@
+use dungeoncrawl::prelude::*;
+
+#[test]
+fn even_the_slowest_monster_earns_energy() {
+    let mut headless = Headless::new(Seed(3), Movement::FourWay);
+    let monster = headless.game.ecs.push((
+        Enemy,
+        Point::new(0, 0),
+        Health {
+            current: 10,
+            max: 10,
+        },
+        Name("Statue".to_string()),
+        Speed(0),
+        Energy(0),
+    ));
+
+    headless.press(VirtualKeyCode::Space, Point::zero());
+
+    let energy = *headless
+        .game
+        .ecs
+        .entry_ref(monster)
+        .unwrap()
+        .get_component::<Energy>()
+        .unwrap();
+    assert!(energy.0 > 0);
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use common::*;
+use dungeoncrawl::prelude::*;
+
+// enough to see a few levels and fights without the suite crawling in debug builds
+const LONG_RUN_TURNS: usize = 3000;
+
+#[test]
+fn scripted_run_is_reproducible() {
+    let play = || {
+        let mut headless = Headless::new(Seed(1234), Movement::FourWay);
+        headless.run(&SCRIPT, 200);
+        end_state(&headless)
+    };
+    let first = play();
+    assert!(first.4.len() > 1, "the run should have logged something");
+    assert_eq!(first, play());
+    assert_eq!(first, play());
+}
+
+#[test]
+fn diagonal_runs_are_reproducible_too() {
+    let play = || {
+        let mut headless = Headless::new(Seed(99), Movement::EightWay);
+        headless.run(&SCRIPT, 200);
+        end_state(&headless)
+    };
+    assert_eq!(play(), play());
+}
+
+#[test]
+fn thousands_of_random_turns_do_not_panic() {
+    let mut rng = RandomNumberGenerator::seeded(7);
+    let mut headless = Headless::new(Seed(7), Movement::FourWay);
+    for _ in 0..LONG_RUN_TURNS {
+        if !headless.game.turn_state().accepts_input() {
+            headless.game.reset();
+        }
+        let key = *rng.random_slice_entry(&SCRIPT).unwrap();
+        headless.press(key, Point::zero());
+    }
+}
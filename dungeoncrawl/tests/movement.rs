@@ -0,0 +1,64 @@
+use dungeoncrawl::prelude::*;
+
+// a monster next to a closed door, told to walk into it; whether the door opened
+fn door_opens(opens_doors: bool) -> bool {
+    let mut headless = Headless::new(Seed(3), Movement::FourWay);
+    let player_pos = <&Point>::query()
+        .filter(component::<Player>())
+        .iter(&headless.game.ecs)
+        .cloned()
+        .next()
+        .unwrap();
+    let (start, door) = (player_pos + Point::new(2, 0), player_pos + Point::new(3, 0));
+    {
+        let mut map = headless.game.resources.get_mut::<Map>().unwrap();
+        let idx = map.point2d_to_index(start);
+        map.tiles[idx] = TileType::Floor;
+        let idx = map.point2d_to_index(door);
+        map.tiles[idx] = TileType::Door { open: false };
+    }
+    let monster = headless.game.ecs.push((
+        Enemy,
+        start,
+        Health {
+            current: 10,
+            max: 10,
+        },
+        Name("Pusher".to_string()),
+    ));
+    if opens_doors {
+        headless
+            .game
+            .ecs
+            .entry(monster)
+            .unwrap()
+            .add_component(OpensDoors);
+    }
+    headless.game.ecs.push((
+        (),
+        WantsToMove {
+            entity: monster,
+            destination: door,
+        },
+    ));
+    headless.game.resources.insert(TurnState::MonsterTurn);
+    headless.game.tick(None, Point::zero(), false);
+
+    // opening the door takes the move either way
+    let pos = *headless
+        .game
+        .ecs
+        .entry_ref(monster)
+        .unwrap()
+        .get_component::<Point>()
+        .unwrap();
+    assert_eq!(pos, start);
+    let map = headless.game.resources.get::<Map>().unwrap();
+    map.tiles[map.point2d_to_index(door)] == TileType::Door { open: true }
+}
+
+#[test]
+fn only_door_openers_open_doors() {
+    assert!(!door_opens(false));
+    assert!(door_opens(true));
+}
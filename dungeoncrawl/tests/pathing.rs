@@ -0,0 +1,69 @@
+use dungeoncrawl::prelude::*;
+
+// a room split by a wall with a single gap at the bottom
+fn split_room(gap: TileType) -> Map {
+    let mut map = Map::new(12, 6);
+    for y in 0..6 {
+        let idx = map.point2d_to_index(Point::new(6, y));
+        map.tiles[idx] = if y == 5 { gap } else { TileType::Wall };
+    }
+    map
+}
+
+fn walk(pathing: &Pathing, from: Point, to: Point) -> Vec<Point> {
+    let mut route = vec![from];
+    let mut pos = from;
+    while pos != to && route.len() < 50 {
+        pos = pathing.next_step(pos, to).unwrap();
+        route.push(pos);
+    }
+    route
+}
+
+#[test]
+fn next_step_finds_the_way_around() {
+    let map = split_room(TileType::Floor);
+    let pathing = map.pathing(Mobility::SAFE);
+    let route = walk(&pathing, Point::new(2, 1), Point::new(10, 1));
+
+    assert_eq!(route.last(), Some(&Point::new(10, 1)));
+    assert!(route.contains(&Point::new(6, 5)));
+    assert!(route
+        .windows(2)
+        .all(|step| DistanceAlg::Manhattan.distance2d(step[0], step[1]) == 1.0));
+}
+
+#[test]
+fn next_step_gives_up_when_cut_off() {
+    let map = split_room(TileType::Lava);
+    assert_eq!(
+        map.pathing(Mobility::SAFE)
+            .next_step(Point::new(2, 1), Point::new(10, 1)),
+        None
+    );
+    assert!(map
+        .pathing(Mobility::PLAYER)
+        .next_step(Point::new(2, 1), Point::new(10, 1))
+        .is_some());
+}
+
+#[test]
+fn next_step_goes_around_rubble_when_that_is_cheaper() {
+    // a thick wall with a rubble-choked gap and an open one a row further down
+    let mut map = Map::new(12, 6);
+    for x in 6..8 {
+        for y in 0..6 {
+            let idx = map.point2d_to_index(Point::new(x, y));
+            map.tiles[idx] = match y {
+                4 => TileType::Rubble,
+                5 => TileType::Floor,
+                _ => TileType::Wall,
+            };
+        }
+    }
+    assert_eq!(
+        map.pathing(Mobility::SAFE)
+            .next_step(Point::new(5, 4), Point::new(8, 4)),
+        Some(Point::new(5, 5))
+    );
+}
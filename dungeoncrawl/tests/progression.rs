@@ -0,0 +1,178 @@
+use dungeoncrawl::prelude::*;
+
+fn plan(architects: Vec<ArchitectKind>, size: (i32, i32)) -> LevelPlan {
+    LevelPlan {
+        name: "Test Level".to_string(),
+        architects,
+        themes: vec![ThemeKind::Dungeon],
+        templates: Vec::new(),
+        spawns: 10,
+        vaults: Vec::new(),
+        bosses: Vec::new(),
+        size: Some(size),
+    }
+}
+
+fn validate(progression: &Progression) -> Result<(), String> {
+    progression.validate(&Templates::load(), &Vaults::load())
+}
+
+#[test]
+fn shipped_levels_are_valid() {
+    let templates = Templates::load();
+    let vaults = Vaults::load();
+    let progression = Progression::load(&templates, &vaults);
+    assert_eq!(progression.validate(&templates, &vaults), Ok(()));
+}
+
+#[test]
+fn levels_can_be_smaller_than_the_screen() {
+    let progression = Progression {
+        levels: vec![plan(vec![ArchitectKind::Rooms], (20, 10))],
+    };
+    assert_eq!(validate(&progression), Ok(()));
+
+    let progression = Progression {
+        levels: vec![plan(vec![ArchitectKind::Rooms], (8, 6))],
+    };
+    assert_eq!(
+        validate(&progression),
+        Err("Test Level is 8x6, smaller than the 10x8 minimum".to_string())
+    );
+
+    let progression = Progression {
+        levels: vec![plan(Vec::new(), MIN_LEVEL_SIZE)],
+    };
+    assert_eq!(
+        validate(&progression),
+        Err("Test Level has no architects".to_string())
+    );
+}
+
+#[test]
+fn levels_make_room_for_their_own_vaults() {
+    // the fortress is 12 by 11 and may be turned either way
+    let mut level = plan(vec![ArchitectKind::Rooms], (20, 14));
+    level.vaults = vec!["Fortress".to_string()];
+    let progression = Progression {
+        levels: vec![level.clone()],
+    };
+    assert_eq!(
+        validate(&progression),
+        Err("Test Level is 20x14, too small for the Fortress vault".to_string())
+    );
+
+    level.size = Some((20, 15));
+    let progression = Progression {
+        levels: vec![level],
+    };
+    assert_eq!(validate(&progression), Ok(()));
+}
+
+#[test]
+fn rejects_misspelt_names() {
+    let mut level = plan(vec![ArchitectKind::Rooms], MIN_LEVEL_SIZE);
+    level.templates = vec!["Goblin".to_string(), "Gobiln".to_string()];
+    let progression = Progression {
+        levels: vec![level.clone()],
+    };
+    assert_eq!(
+        validate(&progression),
+        Err("Test Level has no template named Gobiln".to_string())
+    );
+
+    level.templates = vec!["Goblin".to_string()];
+    level.bosses = vec!["Grubnak".to_string()];
+    let progression = Progression {
+        levels: vec![level.clone()],
+    };
+    assert_eq!(
+        validate(&progression),
+        Err("Test Level has no template named Grubnak".to_string())
+    );
+
+    level.bosses = Vec::new();
+    level.vaults = vec!["Tresury".to_string()];
+    let progression = Progression {
+        levels: vec![level],
+    };
+    assert_eq!(
+        validate(&progression),
+        Err("Test Level has no vault named Tresury".to_string())
+    );
+}
+
+#[test]
+fn every_architect_builds_small_levels() {
+    let vaults = Vaults::load();
+    // caves this size used to wall the player into a pocket with no way out
+    for size in [MIN_LEVEL_SIZE, (40, 25)] {
+        for architect in [
+            ArchitectKind::Rooms,
+            ArchitectKind::Bsp,
+            ArchitectKind::Caves,
+            ArchitectKind::Drunkard,
+        ] {
+            let progression = Progression {
+                levels: vec![plan(vec![architect], size)],
+            };
+            for seed in 0..20 {
+                let mut rng = RandomNumberGenerator::seeded(seed);
+                let mb = MapBuilder::new(&mut rng, &progression, &vaults, 0);
+                let start = mb.map.point2d_to_index(mb.player_start);
+                let exit = mb.map.point2d_to_index(mb.amulet_start);
+                assert_eq!((mb.map.width, mb.map.height), size);
+                assert_ne!(start, exit);
+                assert!(mb.map.distances(&[start]).map[exit] < f32::MAX);
+            }
+        }
+    }
+}
+
+#[test]
+fn monsters_keep_off_traps_and_the_amulet() {
+    let vaults = Vaults::load();
+    for architect in [
+        ArchitectKind::Rooms,
+        ArchitectKind::Bsp,
+        ArchitectKind::Caves,
+        ArchitectKind::Drunkard,
+    ] {
+        // ask for far more monsters than the architect places, so most are topped up
+        let mut level = plan(vec![architect], (40, 25));
+        level.spawns = 400;
+        let progression = Progression {
+            levels: vec![level],
+        };
+        for seed in 0..20 {
+            let mut rng = RandomNumberGenerator::seeded(seed);
+            let mb = MapBuilder::new(&mut rng, &progression, &vaults, 0);
+            mb.monster_spawns.iter().enumerate().for_each(|(i, pt)| {
+                assert_ne!(*pt, mb.amulet_start);
+                assert!(!mb.trap_spawns.contains(pt));
+                assert!(!mb.item_spawns.contains(pt));
+                assert!(!mb.monster_spawns[i + 1..].contains(pt));
+            });
+        }
+    }
+}
+
+#[test]
+fn no_descent_below_the_amulet() {
+    let mut headless = Headless::new(Seed(3), Movement::FourWay);
+    let amulet_level = headless
+        .game
+        .resources
+        .get::<Progression>()
+        .unwrap()
+        .amulet_level();
+    <&mut Player>::query()
+        .iter_mut(&mut headless.game.ecs)
+        .for_each(|player| player.map_level = amulet_level);
+
+    headless.game.resources.insert(TurnState::NextLevel);
+    let state = headless.step(None, Point::zero(), false);
+
+    assert_eq!(state, TurnState::AwaitingInput);
+    assert_eq!(headless.map_level(), amulet_level);
+}
@@ -0,0 +1,60 @@
+mod common;
+
+use common::*;
+use dungeoncrawl::prelude::*;
+use std::fs::{remove_file, write};
+
+// plenty for the random walk below to get the player killed or find the amulet
+const MAX_PRESSES: usize = 20000;
+
+fn temp_path(name: &str) -> String {
+    std::env::temp_dir()
+        .join(format!("dungeoncrawl-{}-{}", std::process::id(), name))
+        .to_string_lossy()
+        .to_string()
+}
+
+#[test]
+fn replay_reaches_the_recorded_outcome() {
+    let seed = Seed(2024);
+    let mut headless = Headless::new(seed, Movement::FourWay);
+    headless.game.recorder = Some(Replay::new(seed, Movement::FourWay));
+    let mut rng = RandomNumberGenerator::seeded(2024);
+    for _ in 0..MAX_PRESSES {
+        if !headless.game.turn_state().accepts_input() {
+            break;
+        }
+        let key = *rng.random_slice_entry(&SCRIPT).unwrap();
+        headless.press(key, Point::zero());
+    }
+    let outcome = headless.game.turn_state();
+    assert!(!outcome.accepts_input(), "the run should have ended");
+
+    let mut recorder = headless.game.recorder.take().unwrap();
+    recorder.outcome = Some(outcome);
+    let path = temp_path("replay.ron");
+    recorder.write(&path);
+    let replay = Replay::load(&path).expect("the replay we just wrote should load");
+    remove_file(&path).unwrap();
+
+    let mut replayed = Headless::from_replay(&replay);
+    assert_eq!(Some(replayed.replay(&replay)), replay.outcome);
+    assert_eq!(end_state(&replayed), end_state(&headless));
+}
+
+#[test]
+fn damaged_replay_is_an_error() {
+    let path = temp_path("damaged-replay.ron");
+    let mut replay = Replay::new(Seed(1), Movement::FourWay);
+    replay.record(Some(VirtualKeyCode::Right), Point::zero(), false);
+    replay.write(&path);
+    let contents = std::fs::read_to_string(&path).unwrap();
+    write(&path, contents.replace("\"Right\"", "\"NotAKey\"")).unwrap();
+    let err = Replay::load(&path)
+        .err()
+        .expect("an unknown key should be rejected");
+    assert!(err.contains("NotAKey"));
+    write(&path, "not a replay").unwrap();
+    assert!(Replay::load(&path).is_err());
+    remove_file(&path).unwrap();
+}
@@ -0,0 +1,51 @@
+mod common;
+
+use common::*;
+use dungeoncrawl::prelude::*;
+use std::fs::{remove_file, write};
+
+fn temp_path(name: &str) -> String {
+    std::env::temp_dir()
+        .join(format!("dungeoncrawl-{}-{}", std::process::id(), name))
+        .to_string_lossy()
+        .to_string()
+}
+
+fn named_entities(headless: &Headless) -> usize {
+    <&Name>::query().iter(&headless.game.ecs).count()
+}
+
+#[test]
+fn saved_game_comes_back_as_it_was() {
+    let mut original = Headless::new(Seed(42), Movement::FourWay);
+    original.run(&SCRIPT, 150);
+
+    let path = temp_path("round-trip.ron");
+    SaveGame::capture(&original.game.ecs, &original.game.resources).write(&path);
+    let save = SaveGame::load(&path).expect("the save we just wrote should load");
+    remove_file(&path).unwrap();
+
+    let mut restored = Headless::new(Seed(0), Movement::FourWay);
+    restored.game.load(save);
+
+    assert_eq!(end_state(&original), end_state(&restored));
+    assert_eq!(named_entities(&original), named_entities(&restored));
+    let tiles = |headless: &Headless| {
+        let map = headless.game.resources.get::<Map>().unwrap();
+        (map.width, map.height, map.revealed_tiles.clone())
+    };
+    assert_eq!(tiles(&original), tiles(&restored));
+    assert!(
+        original.game.resources.get::<Map>().unwrap().tiles
+            == restored.game.resources.get::<Map>().unwrap().tiles
+    );
+}
+
+#[test]
+fn damaged_save_is_an_error() {
+    let path = temp_path("damaged.ron");
+    write(&path, "SaveGame( map: oops").unwrap();
+    assert!(SaveGame::load(&path).is_err());
+    remove_file(&path).unwrap();
+    assert!(SaveGame::load(&path).is_err());
+}
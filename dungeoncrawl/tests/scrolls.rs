@@ -0,0 +1,81 @@
+use dungeoncrawl::prelude::*;
+
+fn dummy(headless: &mut Headless, pos: Point) -> Entity {
+    headless.game.ecs.push((
+        Enemy,
+        pos,
+        Health {
+            current: 10,
+            max: 10,
+        },
+        Name("Dummy".to_string()),
+    ))
+}
+
+fn health(headless: &Headless, entity: Entity) -> i32 {
+    headless
+        .game
+        .ecs
+        .entry_ref(entity)
+        .unwrap()
+        .get_component::<Health>()
+        .unwrap()
+        .current
+}
+
+#[test]
+fn fireball_stops_at_walls() {
+    let mut headless = Headless::new(Seed(3), Movement::FourWay);
+    let (player, center) = <(Entity, &Point)>::query()
+        .filter(component::<Player>())
+        .iter(&headless.game.ecs)
+        .map(|(entity, pos)| (*entity, *pos))
+        .next()
+        .unwrap();
+    // clear the other monsters out of the way
+    let monsters: Vec<Entity> = <Entity>::query()
+        .filter(component::<Enemy>())
+        .iter(&headless.game.ecs)
+        .cloned()
+        .collect();
+    monsters.into_iter().for_each(|monster| {
+        headless.game.ecs.remove(monster);
+    });
+
+    {
+        let mut map = headless.game.resources.get_mut::<Map>().unwrap();
+        for (x, y, tile) in [
+            (1, 0, TileType::Wall),
+            (2, 0, TileType::Floor),
+            (0, 1, TileType::Floor),
+            (0, 2, TileType::Floor),
+        ] {
+            let idx = map.point2d_to_index(center + Point::new(x, y));
+            map.tiles[idx] = tile;
+        }
+    }
+    let sheltered = dummy(&mut headless, center + Point::new(2, 0));
+    let exposed = dummy(&mut headless, center + Point::new(0, 2));
+
+    let scroll = headless.game.ecs.push((
+        Item,
+        Provides(vec![Effect::Fireball {
+            damage: 3,
+            radius: 3,
+        }]),
+        Name("Test Fireball".to_string()),
+    ));
+    headless.game.ecs.push((
+        (),
+        ActivateItem {
+            used_by: player,
+            item: scroll,
+            target: Some(center),
+        },
+    ));
+    headless.game.resources.insert(TurnState::PlayerTurn);
+    headless.step(None, Point::zero(), false);
+
+    assert_eq!(health(&headless, sheltered), 10);
+    assert_eq!(health(&headless, exposed), 7);
+}
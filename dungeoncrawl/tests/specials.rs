@@ -0,0 +1,167 @@
+use dungeoncrawl::prelude::*;
+
+fn summons(headless: &Headless) -> usize {
+    let log = headless.game.resources.get::<GameLog>().unwrap();
+    log.entries
+        .iter()
+        .filter(|event| matches!(event, GameEvent::Summoned { .. }))
+        .count()
+}
+
+#[test]
+fn summons_keep_out_of_hazards() {
+    let mut headless = Headless::new(Seed(3), Movement::FourWay);
+    let player_pos = <&Point>::query()
+        .filter(component::<Player>())
+        .iter(&headless.game.ecs)
+        .cloned()
+        .next()
+        .unwrap();
+    let monsters: Vec<Entity> = <Entity>::query()
+        .filter(component::<Enemy>())
+        .iter(&headless.game.ecs)
+        .cloned()
+        .collect();
+    monsters.into_iter().for_each(|monster| {
+        headless.game.ecs.remove(monster);
+    });
+
+    // the caster's only safe neighbour is the tile between it and the player
+    let caster_pos = player_pos + Point::new(2, 0);
+    let hazards = [
+        (Point::new(1, 0), TileType::Lava),
+        (Point::new(0, -1), TileType::Chasm),
+        (Point::new(0, 1), TileType::Door { open: false }),
+    ];
+    {
+        let mut map = headless.game.resources.get_mut::<Map>().unwrap();
+        for (x, y, tile) in [
+            (1, 0, TileType::Floor),
+            (2, 0, TileType::Floor),
+            (1, -1, TileType::Wall),
+            (1, 1, TileType::Wall),
+        ] {
+            let idx = map.point2d_to_index(player_pos + Point::new(x, y));
+            map.tiles[idx] = tile;
+        }
+        for (delta, tile) in hazards.iter() {
+            let idx = map.point2d_to_index(caster_pos + *delta);
+            map.tiles[idx] = *tile;
+        }
+    }
+    let caster = headless.game.ecs.push((
+        Enemy,
+        caster_pos,
+        Health {
+            current: 100,
+            max: 100,
+        },
+        Name("Caster".to_string()),
+        FieldOfView::new(6),
+        SpecialAttacks {
+            attacks: vec![SpecialAttack::Summon {
+                template: "Goblin".to_string(),
+                count: 4,
+            }],
+            cooldown: 0,
+            recharge: 0,
+        },
+    ));
+    // monsters only get a turn when they have the energy for it
+    let mut entry = headless.game.ecs.entry(caster).unwrap();
+    entry.add_component(Speed(NORMAL_SPEED));
+    entry.add_component(Energy(ACTION_COST));
+
+    for _ in 0..30 {
+        if summons(&headless) > 0 {
+            break;
+        }
+        headless.press(VirtualKeyCode::Space, Point::zero());
+    }
+
+    assert_eq!(summons(&headless), 1);
+    let landed: Vec<Point> = <(&Point, &Name)>::query()
+        .filter(component::<Enemy>())
+        .iter(&headless.game.ecs)
+        .filter(|(_, name)| name.0 == "Goblin")
+        .map(|(pos, _)| *pos)
+        .collect();
+    assert_eq!(landed, vec![player_pos + Point::new(1, 0)]);
+}
+
+#[test]
+fn special_costs_one_action() {
+    let mut headless = Headless::new(Seed(3), Movement::FourWay);
+    let player_pos = <&Point>::query()
+        .filter(component::<Player>())
+        .iter(&headless.game.ecs)
+        .cloned()
+        .next()
+        .unwrap();
+    let monsters: Vec<Entity> = <Entity>::query()
+        .filter(component::<Enemy>())
+        .iter(&headless.game.ecs)
+        .cloned()
+        .collect();
+    monsters.into_iter().for_each(|monster| {
+        headless.game.ecs.remove(monster);
+    });
+    {
+        let mut map = headless.game.resources.get_mut::<Map>().unwrap();
+        let idx = map.point2d_to_index(player_pos + Point::new(1, 0));
+        map.tiles[idx] = TileType::Floor;
+    }
+    let caster = headless.game.ecs.push((
+        Enemy,
+        player_pos + Point::new(1, 0),
+        Health {
+            current: 100,
+            max: 100,
+        },
+        Name("Caster".to_string()),
+        FieldOfView::new(6),
+        SpecialAttacks {
+            attacks: vec![SpecialAttack::Slam {
+                damage: 0,
+                radius: 2,
+            }],
+            cooldown: 0,
+            recharge: 0,
+        },
+        Speed(0),
+    ));
+
+    // a fast monster banks two actions; the special should only spend one of them
+    let mut used = false;
+    for _ in 0..30 {
+        headless
+            .game
+            .ecs
+            .entry(caster)
+            .unwrap()
+            .add_component(Energy(ACTION_COST * 2));
+        headless.game.resources.insert(TurnState::MonsterTurn);
+        headless.game.tick(None, Point::zero(), false);
+        used = headless
+            .game
+            .resources
+            .get::<GameLog>()
+            .unwrap()
+            .entries
+            .iter()
+            .any(|event| matches!(event, GameEvent::UsedSpecial { .. }));
+        if used {
+            break;
+        }
+    }
+
+    assert!(used);
+    let energy = *headless
+        .game
+        .ecs
+        .entry_ref(caster)
+        .unwrap()
+        .get_component::<Energy>()
+        .unwrap();
+    assert_eq!(energy, Energy(ACTION_COST));
+}
@@ -0,0 +1,74 @@
+use dungeoncrawl::prelude::*;
+
+const DAGGER_RANGE: i32 = 4;
+
+fn player(headless: &Headless) -> (Entity, Point) {
+    <(Entity, &Point)>::query()
+        .filter(component::<Player>())
+        .iter(&headless.game.ecs)
+        .map(|(entity, pos)| (*entity, *pos))
+        .next()
+        .unwrap()
+}
+
+// a visible floor tile in throwing range with nobody on it
+fn open_tile(headless: &Headless) -> Point {
+    let (player, player_pos) = player(headless);
+    let ecs = &headless.game.ecs;
+    let map = headless.game.resources.get::<Map>().unwrap();
+    let occupied: Vec<Point> = <&Point>::query()
+        .filter(component::<Health>())
+        .iter(ecs)
+        .cloned()
+        .collect();
+    let fov = ecs
+        .entry_ref(player)
+        .unwrap()
+        .get_component::<FieldOfView>()
+        .unwrap()
+        .clone();
+    let mut tiles: Vec<Point> = fov
+        .visible_tiles
+        .iter()
+        .filter(|pt| **pt != player_pos && map.can_enter_tile(**pt) && !occupied.contains(pt))
+        .filter(|pt| in_range(&map, player_pos, **pt, DAGGER_RANGE))
+        .cloned()
+        .collect();
+    tiles.sort_by_key(|pt| (pt.x, pt.y));
+    tiles[0]
+}
+
+#[test]
+fn a_throw_can_be_aimed_at_an_empty_tile() {
+    let mut headless = Headless::new(Seed(11), Movement::FourWay);
+    // let the first field of view be worked out
+    headless.press(VirtualKeyCode::Space, Point::zero());
+    let (player, _) = player(&headless);
+    let dagger = headless.game.ecs.push((
+        Item,
+        Throwable,
+        Ranged {
+            range: DAGGER_RANGE,
+        },
+        Damage(1),
+        Name("Test Dagger".to_string()),
+        Carried(player),
+    ));
+
+    let tile = open_tile(&headless);
+    assert_eq!(
+        headless.press(VirtualKeyCode::T, Point::zero()),
+        TurnState::Targeting
+    );
+    let mouse = {
+        let camera = headless.game.resources.get::<Camera>().unwrap();
+        tile - Point::new(camera.left_x, camera.top_y)
+    };
+    // the first click picks the tile, the second throws
+    assert_eq!(headless.step(None, mouse, true), TurnState::Targeting);
+    headless.step(None, mouse, true);
+
+    let entry = headless.game.ecs.entry_ref(dagger).unwrap();
+    assert_eq!(entry.get_component::<Point>().ok().cloned(), Some(tile));
+    assert!(entry.get_component::<Carried>().is_err());
+}
@@ -0,0 +1,34 @@
+use dungeoncrawl::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+fn vault(layout: &str) -> Vaults {
+    Vaults {
+        vaults: vec![Vault {
+            name: "Test Vault".to_string(),
+            levels: HashSet::new(),
+            frequency: 1,
+            layout: layout.to_string(),
+            legend: HashMap::new(),
+            rotate: false,
+            mirror: false,
+        }],
+    }
+}
+
+#[test]
+fn shipped_vaults_are_valid() {
+    assert_eq!(Vaults::load().validate(), Ok(()));
+}
+
+#[test]
+fn rejects_bad_layouts() {
+    assert_eq!(vault("\n ---\n -#-\n ---\n").validate(), Ok(()));
+    assert_eq!(
+        vault("\n ---\n -#\n ---\n").validate(),
+        Err("Test Vault has rows of different lengths".to_string())
+    );
+    assert_eq!(
+        vault("\n ---\n -x-\n ---\n").validate(),
+        Err("Test Vault has no legend entry for [x]".to_string())
+    );
+}